@@ -1,37 +1,194 @@
-use std::sync::{Arc, OnceLock};
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
 
+use chrono::{DateTime, Utc};
+use dashmap::mapref::entry::Entry;
 use dashmap::DashMap;
 
-use crate::issue::{CodeType, Issue, IssueRecord};
+use crate::issue::{CodeType, Issue, IssueRecord, Severity};
 
+/// Change of an issue's state in the dashboard
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DashboardEvent {
+    Added(CodeType),
+    Solved(CodeType),
+    /// Removed to make room for a new issue in a dashboard with limited capacity
+    Evicted(CodeType),
+}
+
+pub type DashboardSubscriber = Arc<dyn Fn(DashboardEvent) + Send + Sync>;
+
+/// A set of active issues. The free functions of this module operate on a process-wide instance,
+/// independent instances can be created with `Dashboard::default()`.
 #[derive(Default)]
-struct Dashboard {
-    pub issues: DashMap<CodeType, IssueRecord>,
+pub struct Dashboard {
+    issues: DashMap<CodeType, IssueRecord>,
+    subscribers: RwLock<Vec<DashboardSubscriber>>,
+    /// Maximum number of active issues, the oldest ones are evicted to make room for new ones
+    max_issues: Option<usize>,
 }
 
 impl Dashboard {
-    /// Activates an issue, returning true if the issue was not active before
-    fn add_issue(&self, issue: impl Issue) -> bool {
+    /// Creates a dashboard holding at most `max_issues` active issues.
+    /// The limit is best-effort under concurrent submissions.
+    pub fn with_max_issues(max_issues: usize) -> Self {
+        assert!(max_issues > 0, "max_issues must be positive");
+        Self {
+            max_issues: Some(max_issues),
+            ..Default::default()
+        }
+    }
+
+    /// Activates an issue, returning true if the issue was not active before.
+    /// An active issue keeps its original record, only `last_seen` and `occurrences` change.
+    pub fn add_issue(&self, issue: impl Issue + 'static) -> bool {
+        let (added, _evicted) = self.add_issue_with_eviction(issue);
+        added
+    }
+
+    /// Same as [`Dashboard::add_issue`], but also returns the oldest issue if it was evicted
+    /// to stay within the capacity limit.
+    pub fn add_issue_with_eviction(
+        &self,
+        issue: impl Issue + 'static,
+    ) -> (bool, Option<IssueRecord>) {
         let code = issue.code();
-        if self.issues.contains_key(&code) {
-            return false;
+        match self.issues.entry(code.clone()) {
+            Entry::Occupied(mut existing) => {
+                let existing = existing.get_mut();
+                existing.last_seen = Utc::now();
+                existing.occurrences += 1;
+                return (false, None);
+            }
+            Entry::Vacant(vacant) => {
+                vacant.insert(IssueRecord::from(issue));
+            }
+        }
+        self.notify(DashboardEvent::Added(code.clone()));
+        // the entry guard is released, evicting may lock any shard
+        let evicted = self.evict_over_capacity(&code);
+        (true, evicted)
+    }
+
+    /// Removes the oldest issue other than `keep` if the dashboard is over capacity
+    fn evict_over_capacity(&self, keep: &str) -> Option<IssueRecord> {
+        let max_issues = self.max_issues?;
+        if self.issues.len() <= max_issues {
+            return None;
         }
-        let issue = IssueRecord::from(issue);
-        self.issues.insert(code, issue).is_none()
+        let oldest_code = self
+            .issues
+            .iter()
+            .filter(|kv| kv.key() != keep)
+            .min_by_key(|kv| kv.value().timestamp)
+            .map(|kv| kv.key().clone())?;
+        let (code, issue) = self.issues.remove(&oldest_code)?;
+        self.notify(DashboardEvent::Evicted(code));
+        Some(issue)
     }
 
     /// Deactivates an issue by its code, returning true if the issue was active before
-    fn remove_issue<S: AsRef<str>>(&self, code: S) -> bool {
-        if self.issues.contains_key(code.as_ref()) {
-            return self.issues.remove(code.as_ref()).is_some();
+    pub fn remove_issue<S: AsRef<str>>(&self, code: S) -> bool {
+        self.take_issue(code).is_some()
+    }
+
+    /// Deactivates an issue by its code, returning its record if the issue was active before
+    pub fn take_issue<S: AsRef<str>>(&self, code: S) -> Option<IssueRecord> {
+        let (code, issue) = self.issues.remove(code.as_ref())?;
+        self.notify(DashboardEvent::Solved(code));
+        Some(issue)
+    }
+
+    /// Deactivates all issues matching the filter, returning how many were removed
+    pub fn remove_issues_by_filter(&self, filter: impl Fn(&IssueRecord) -> bool) -> usize {
+        let mut removed = Vec::new();
+        self.issues.retain(|code, issue| {
+            let matches = filter(issue);
+            if matches {
+                removed.push(code.clone());
+            }
+            !matches
+        });
+        // notify after `retain` releases the map, so callbacks may access the dashboard
+        let count = removed.len();
+        for code in removed {
+            self.notify(DashboardEvent::Solved(code));
         }
-        false
+        count
+    }
+
+    /// Deactivates all issues submitted as the concrete type `I`, returning how many were removed
+    pub fn remove_issues_of_type<I: Issue + 'static>(&self) -> usize {
+        let issue_type = TypeId::of::<I>();
+        self.remove_issues_by_filter(|issue| issue.issue_type == issue_type)
+    }
+
+    /// Deactivates all issues whose ttl has passed, returning how many were removed
+    pub fn sweep_expired(&self) -> usize {
+        self.sweep_expired_at(Utc::now())
+    }
+
+    fn sweep_expired_at(&self, now: DateTime<Utc>) -> usize {
+        self.remove_issues_by_filter(|issue| issue.is_expired_at(now))
     }
 
     /// Returns all issues in the dashboard. This operation clones every issue, so it is more expensive.
-    fn get_all_issues(&self) -> Vec<IssueRecord> {
+    pub fn get_all_issues(&self) -> Vec<IssueRecord> {
         self.issues.iter().map(|kv| kv.value().clone()).collect()
     }
+
+    /// Returns issues with severity of at least `min`
+    pub fn get_issues_by_severity(&self, min: Severity) -> Vec<IssueRecord> {
+        self.issues
+            .iter()
+            .filter(|kv| kv.value().severity >= min)
+            .map(|kv| kv.value().clone())
+            .collect()
+    }
+
+    /// Returns issues submitted as the concrete type `I`
+    pub fn get_issues_of_type<I: Issue + 'static>(&self) -> Vec<IssueRecord> {
+        let issue_type = TypeId::of::<I>();
+        self.issues
+            .iter()
+            .filter(|kv| kv.value().issue_type == issue_type)
+            .map(|kv| kv.value().clone())
+            .collect()
+    }
+
+    /// Returns the number of active issues without cloning them
+    pub fn issue_count(&self) -> usize {
+        self.issues.len()
+    }
+
+    /// Returns the number of active issues per concrete issue type without cloning them
+    pub fn count_by_type(&self) -> HashMap<TypeId, usize> {
+        let mut counts = HashMap::new();
+        for kv in self.issues.iter() {
+            *counts.entry(kv.value().issue_type).or_default() += 1;
+        }
+        counts
+    }
+
+    /// Deactivates all issues, each one is reported to subscribers as solved
+    pub fn clear(&self) {
+        self.remove_issues_by_filter(|_| true);
+    }
+
+    /// Registers a callback invoked on every issue added to, solved in or evicted from
+    /// the dashboard
+    pub fn subscribe(&self, callback: DashboardSubscriber) {
+        self.subscribers.write().unwrap().push(callback);
+    }
+
+    fn notify(&self, event: DashboardEvent) {
+        // clone the list, so callbacks can subscribe without deadlocking
+        let subscribers = self.subscribers.read().unwrap().clone();
+        for subscriber in subscribers {
+            subscriber(event.clone());
+        }
+    }
 }
 
 fn dashboard() -> Arc<Dashboard> {
@@ -42,7 +199,7 @@ fn dashboard() -> Arc<Dashboard> {
 }
 
 /// Submits an issue to the dashboard, returning true if the issue code was not active before
-pub fn submit(issue: impl Issue) -> bool {
+pub fn submit(issue: impl Issue + 'static) -> bool {
     dashboard().add_issue(issue)
 }
 
@@ -51,34 +208,361 @@ pub fn solve<S: AsRef<str>>(code: S) -> bool {
     dashboard().remove_issue(code)
 }
 
+/// Solves an issue by its code, returning its record if the issue code was active before
+pub fn solve_and_take<S: AsRef<str>>(code: S) -> Option<IssueRecord> {
+    dashboard().take_issue(code)
+}
+
+/// Solves all issues matching the filter, returning how many were solved
+pub fn solve_by_filter(filter: impl Fn(&IssueRecord) -> bool) -> usize {
+    dashboard().remove_issues_by_filter(filter)
+}
+
+/// Solves all issues submitted as the concrete type `I`, returning how many were solved
+pub fn solve_all_of_type<I: Issue + 'static>() -> usize {
+    dashboard().remove_issues_of_type::<I>()
+}
+
+/// Solves transient issues not re-submitted within their ttl, returning how many were solved
+pub fn sweep_expired() -> usize {
+    dashboard().sweep_expired()
+}
+
 pub fn all_issues() -> Vec<IssueRecord> {
     dashboard().get_all_issues()
 }
 
-/// Clears all issues from the dashboard
+/// Returns the number of active issues
+pub fn issue_count() -> usize {
+    dashboard().issue_count()
+}
+
+/// Returns the number of active issues per concrete issue type
+pub fn count_by_type() -> HashMap<TypeId, usize> {
+    dashboard().count_by_type()
+}
+
+/// Returns a JSON snapshot of all issues in the dashboard
+pub fn issues_as_json() -> serde_json::Value {
+    serde_json::to_value(all_issues()).unwrap_or_else(|err| {
+        log::warn!("Failed to serialize issues: {err}");
+        serde_json::Value::Array(vec![])
+    })
+}
+
+/// Returns all issues with severity of at least `min`
+pub fn issues_by_severity(min: Severity) -> Vec<IssueRecord> {
+    dashboard().get_issues_by_severity(min)
+}
+
+/// Returns all issues submitted as the concrete type `I`
+pub fn issues_of_type<I: Issue + 'static>() -> Vec<IssueRecord> {
+    dashboard().get_issues_of_type::<I>()
+}
+
+/// Registers a callback invoked on every issue added to, solved in or evicted from the dashboard
+pub fn subscribe(callback: DashboardSubscriber) {
+    dashboard().subscribe(callback)
+}
+
+/// Clears all issues from the dashboard, each one is reported to subscribers as solved
 pub fn clear() {
-    dashboard().issues.clear();
+    dashboard().clear();
 }
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use serial_test::serial;
 
     use super::*;
     use crate::issue::DummyIssue;
+    use crate::problems::TooManyCollections;
 
     #[test]
     fn test_dashboard() {
         let dashboard = Dashboard::default();
-        let issue = DummyIssue {
-            code: "test".to_string(),
-        };
+        let issue = DummyIssue::new("test");
         assert!(dashboard.add_issue(issue.clone()));
         assert!(!dashboard.add_issue(issue.clone()));
         assert!(dashboard.remove_issue("test"));
         assert!(!dashboard.remove_issue("test"));
     }
 
+    #[test]
+    fn test_take_issue() {
+        let dashboard = Dashboard::default();
+        dashboard.add_issue(DummyIssue::with_severity("test", Severity::Error));
+        dashboard.add_issue(DummyIssue::new("test"));
+
+        let issue = dashboard.take_issue("test").unwrap();
+        assert_eq!(issue.code, "test");
+        assert_eq!(issue.severity, Severity::Error);
+        assert_eq!(issue.occurrences, 2);
+
+        assert!(dashboard.take_issue("test").is_none());
+    }
+
+    #[test]
+    fn test_resubmit_keeps_timestamp() {
+        let dashboard = Dashboard::default();
+        assert!(dashboard.add_issue(DummyIssue::new("test")));
+        let first_timestamp = dashboard.issues.get("test").unwrap().timestamp;
+
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        assert!(!dashboard.add_issue(DummyIssue::new("test")));
+        assert_eq!(
+            dashboard.issues.get("test").unwrap().timestamp,
+            first_timestamp
+        );
+
+        // solving resets the issue, so submitting again starts a new record
+        assert!(dashboard.remove_issue("test"));
+        assert!(dashboard.add_issue(DummyIssue::new("test")));
+        assert!(dashboard.issues.get("test").unwrap().timestamp > first_timestamp);
+    }
+
+    #[test]
+    fn test_issues_by_severity() {
+        let dashboard = Dashboard::default();
+        dashboard.add_issue(DummyIssue::with_severity("info", Severity::Info));
+        dashboard.add_issue(DummyIssue::with_severity("warning", Severity::Warning));
+        dashboard.add_issue(DummyIssue::with_severity("error", Severity::Error));
+        dashboard.add_issue(DummyIssue::with_severity("critical", Severity::Critical));
+
+        let codes = |min| {
+            let mut codes: Vec<_> = dashboard
+                .get_issues_by_severity(min)
+                .into_iter()
+                .map(|issue| issue.code)
+                .collect();
+            codes.sort();
+            codes
+        };
+
+        assert_eq!(codes(Severity::Info).len(), 4);
+        assert_eq!(codes(Severity::Error), vec!["critical", "error"]);
+        assert_eq!(codes(Severity::Critical), vec!["critical"]);
+    }
+
+    #[test]
+    fn test_issues_of_type() {
+        let dashboard = Dashboard::default();
+        dashboard.add_issue(DummyIssue::new("dummy1"));
+        dashboard.add_issue(DummyIssue::new("dummy2"));
+        dashboard.add_issue(TooManyCollections);
+
+        let mut dummy_codes: Vec<_> = dashboard
+            .get_issues_of_type::<DummyIssue>()
+            .into_iter()
+            .map(|issue| issue.code)
+            .collect();
+        dummy_codes.sort();
+        assert_eq!(dummy_codes, vec!["dummy1", "dummy2"]);
+
+        let too_many = dashboard.get_issues_of_type::<TooManyCollections>();
+        assert_eq!(too_many.len(), 1);
+        assert_eq!(too_many[0].code, TooManyCollections.code());
+    }
+
+    #[test]
+    fn test_issue_counts() {
+        let dashboard = Dashboard::default();
+        assert_eq!(dashboard.issue_count(), 0);
+        assert!(dashboard.count_by_type().is_empty());
+
+        dashboard.add_issue(DummyIssue::new("dummy1"));
+        dashboard.add_issue(DummyIssue::new("dummy2"));
+        dashboard.add_issue(DummyIssue::new("dummy2"));
+        dashboard.add_issue(TooManyCollections);
+
+        assert_eq!(dashboard.issue_count(), dashboard.get_all_issues().len());
+        assert_eq!(dashboard.issue_count(), 3);
+
+        let counts = dashboard.count_by_type();
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts[&TypeId::of::<DummyIssue>()], 2);
+        assert_eq!(counts[&TypeId::of::<TooManyCollections>()], 1);
+    }
+
+    #[test]
+    fn test_remove_issues_of_type() {
+        let dashboard = Dashboard::default();
+        dashboard.add_issue(DummyIssue::new("dummy1"));
+        dashboard.add_issue(DummyIssue::new("dummy2"));
+        dashboard.add_issue(TooManyCollections);
+
+        assert_eq!(dashboard.remove_issues_of_type::<DummyIssue>(), 2);
+        assert_eq!(dashboard.remove_issues_of_type::<DummyIssue>(), 0);
+
+        let remaining = dashboard.get_all_issues();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].code, TooManyCollections.code());
+    }
+
+    #[test]
+    fn test_occurrences() {
+        let dashboard = Dashboard::default();
+        assert!(dashboard.add_issue(DummyIssue::new("test")));
+        for _ in 0..4 {
+            assert!(!dashboard.add_issue(DummyIssue::new("test")));
+        }
+
+        let issues = dashboard.get_all_issues();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].occurrences, 5);
+        assert!(issues[0].last_seen >= issues[0].timestamp);
+    }
+
+    #[test]
+    fn test_sweep_expired() {
+        let ttl = Duration::from_millis(100);
+        let dashboard = Dashboard::default();
+        dashboard.add_issue(DummyIssue {
+            ttl: Some(ttl),
+            ..DummyIssue::new("transient")
+        });
+        dashboard.add_issue(DummyIssue::new("persistent"));
+        let last_seen = dashboard.issues.get("transient").unwrap().last_seen;
+
+        assert_eq!(dashboard.sweep_expired_at(last_seen + ttl), 0);
+        assert_eq!(dashboard.sweep_expired_at(last_seen + ttl * 2), 1);
+
+        let remaining = dashboard.get_all_issues();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].code, "persistent");
+    }
+
+    #[test]
+    fn test_resubmit_refreshes_ttl() {
+        let ttl = Duration::from_secs(60);
+        let dashboard = Dashboard::default();
+        let issue = DummyIssue {
+            ttl: Some(ttl),
+            ..DummyIssue::new("transient")
+        };
+        dashboard.add_issue(issue.clone());
+        let first_seen = dashboard.issues.get("transient").unwrap().last_seen;
+
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(!dashboard.add_issue(issue));
+        let last_seen = dashboard.issues.get("transient").unwrap().last_seen;
+        assert!(last_seen > first_seen);
+
+        // expired relative to the first submission, but not to the refreshed one
+        let now = first_seen + ttl + (last_seen - first_seen) / 2;
+        assert_eq!(dashboard.sweep_expired_at(now), 0);
+        assert_eq!(dashboard.sweep_expired_at(last_seen + ttl * 2), 1);
+    }
+
+    #[test]
+    fn test_subscribe() {
+        let dashboard = Dashboard::default();
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let events_clone = events.clone();
+        dashboard.subscribe(Arc::new(move |event| {
+            events_clone.lock().unwrap().push(event);
+        }));
+
+        dashboard.add_issue(DummyIssue::new("issue1"));
+        dashboard.add_issue(DummyIssue::new("issue1"));
+        dashboard.add_issue(DummyIssue::new("issue2"));
+        dashboard.remove_issue("issue1");
+        dashboard.remove_issue("issue1");
+        dashboard.remove_issues_of_type::<DummyIssue>();
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                DashboardEvent::Added("issue1".to_string()),
+                DashboardEvent::Added("issue2".to_string()),
+                DashboardEvent::Solved("issue1".to_string()),
+                DashboardEvent::Solved("issue2".to_string()),
+            ]
+        );
+
+        // clearing reports every active issue as solved
+        events.lock().unwrap().clear();
+        dashboard.add_issue(DummyIssue::new("issue3"));
+        dashboard.clear();
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                DashboardEvent::Added("issue3".to_string()),
+                DashboardEvent::Solved("issue3".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_independent_dashboards() {
+        let dashboard1 = Dashboard::default();
+        let dashboard2 = Dashboard::default();
+
+        assert!(dashboard1.add_issue(DummyIssue::new("issue1")));
+        assert!(dashboard2.add_issue(DummyIssue::new("issue1")));
+        assert!(dashboard2.add_issue(DummyIssue::new("issue2")));
+
+        assert_eq!(
+            dashboard1.remove_issues_by_filter(|issue| issue.code == "issue1"),
+            1
+        );
+        assert!(dashboard1.get_all_issues().is_empty());
+        assert_eq!(dashboard2.get_all_issues().len(), 2);
+
+        dashboard2.clear();
+        assert!(dashboard2.get_all_issues().is_empty());
+    }
+
+    #[test]
+    fn test_max_issues_evicts_oldest() {
+        let dashboard = Dashboard::with_max_issues(3);
+        let mut evicted = Vec::new();
+        for i in 1..=5 {
+            let (added, evicted_issue) =
+                dashboard.add_issue_with_eviction(DummyIssue::new(format!("issue{i}")));
+            assert!(added);
+            evicted.extend(evicted_issue.map(|issue| issue.code));
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(evicted, vec!["issue1", "issue2"]);
+
+        let mut codes: Vec<_> = dashboard
+            .get_all_issues()
+            .into_iter()
+            .map(|issue| issue.code)
+            .collect();
+        codes.sort();
+        assert_eq!(codes, vec!["issue3", "issue4", "issue5"]);
+
+        // re-submitting an active issue doesn't evict anything
+        let (added, evicted_issue) = dashboard.add_issue_with_eviction(DummyIssue::new("issue3"));
+        assert!(!added);
+        assert!(evicted_issue.is_none());
+    }
+
+    #[test]
+    fn test_concurrent_add_counts_every_occurrence() {
+        let dashboard = Arc::new(Dashboard::default());
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let dashboard = dashboard.clone();
+                std::thread::spawn(move || {
+                    (0..100)
+                        .filter(|_| dashboard.add_issue(DummyIssue::new("issue")))
+                        .count()
+                })
+            })
+            .collect();
+        let added: usize = handles.into_iter().map(|h| h.join().unwrap()).sum();
+
+        assert_eq!(added, 1);
+        let issues = dashboard.get_all_issues();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].occurrences, 800);
+    }
+
     #[test]
     #[serial]
     fn test_singleton() -> std::thread::Result<()> {
@@ -110,4 +594,27 @@ mod tests {
         clear();
         Ok(())
     }
+
+    #[test]
+    #[serial]
+    fn test_issues_as_json() {
+        clear();
+
+        submit(DummyIssue::new("issue1"));
+        submit(DummyIssue::with_severity("issue2", Severity::Critical));
+
+        let json = issues_as_json();
+        let issues = json.as_array().unwrap();
+        assert_eq!(issues.len(), 2);
+
+        let issue2 = issues
+            .iter()
+            .find(|issue| issue["code"] == "issue2")
+            .unwrap();
+        assert_eq!(issue2["severity"], "critical");
+        assert!(issue2["timestamp"].is_string());
+        assert!(issue2.get("issue_type").is_none());
+
+        clear();
+    }
 }
@@ -1,4 +1,6 @@
+use std::any::TypeId;
 use std::fmt::Debug;
+use std::time::Duration;
 
 use chrono::{DateTime, Utc};
 use schemars::JsonSchema;
@@ -9,10 +11,30 @@ use crate::solution::Solution;
 /// Type of the issue code
 pub type CodeType = String;
 
+/// How serious an issue is, from least to most severe
+#[derive(Debug, Default, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Info,
+    #[default]
+    Warning,
+    Error,
+    Critical,
+}
+
 pub trait Issue {
     fn code(&self) -> CodeType;
     fn description(&self) -> String;
     fn solution(&self) -> Solution;
+
+    fn severity(&self) -> Severity {
+        Severity::default()
+    }
+
+    /// Transient issues expire if not re-submitted within this window
+    fn ttl(&self) -> Option<Duration> {
+        None
+    }
 }
 
 /// An issue that can be identified by its code
@@ -21,16 +43,45 @@ pub struct IssueRecord {
     pub code: CodeType,
     pub description: String,
     pub solution: Solution,
+    pub severity: Severity,
+    /// When the issue first became active. Re-submitting an active issue keeps it unchanged.
     pub timestamp: DateTime<Utc>,
+    /// When the issue was last submitted
+    pub last_seen: DateTime<Utc>,
+    /// How many times the issue was submitted while active
+    pub occurrences: u64,
+    /// Time after `last_seen` at which the issue expires, if it is transient
+    #[serde(skip)]
+    pub(crate) ttl: Option<Duration>,
+    /// Concrete type of the submitted issue
+    #[serde(skip)]
+    pub(crate) issue_type: TypeId,
 }
 
-impl<I: Issue> From<I> for IssueRecord {
+impl IssueRecord {
+    /// Whether the issue's ttl has passed at `now`. Issues without ttl never expire.
+    pub fn is_expired_at(&self, now: DateTime<Utc>) -> bool {
+        self.ttl.is_some_and(|ttl| {
+            now.signed_duration_since(self.last_seen)
+                .to_std()
+                .is_ok_and(|elapsed| elapsed > ttl)
+        })
+    }
+}
+
+impl<I: Issue + 'static> From<I> for IssueRecord {
     fn from(val: I) -> Self {
+        let now = Utc::now();
         Self {
             code: val.code(),
             description: val.description(),
             solution: val.solution(),
-            timestamp: Utc::now(),
+            severity: val.severity(),
+            timestamp: now,
+            last_seen: now,
+            occurrences: 1,
+            ttl: val.ttl(),
+            issue_type: TypeId::of::<I>(),
         }
     }
 }
@@ -38,12 +89,26 @@ impl<I: Issue> From<I> for IssueRecord {
 #[derive(Clone)]
 pub(crate) struct DummyIssue {
     pub code: String,
+    pub severity: Severity,
+    pub ttl: Option<Duration>,
 }
 
+#[cfg(test)]
 impl DummyIssue {
-    #[cfg(test)]
     pub fn new(code: impl Into<String>) -> Self {
-        Self { code: code.into() }
+        Self {
+            code: code.into(),
+            severity: Severity::default(),
+            ttl: None,
+        }
+    }
+
+    pub fn with_severity(code: impl Into<String>, severity: Severity) -> Self {
+        Self {
+            code: code.into(),
+            severity,
+            ttl: None,
+        }
     }
 }
 
@@ -59,4 +124,12 @@ impl Issue for DummyIssue {
     fn solution(&self) -> Solution {
         Solution::None
     }
+
+    fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    fn ttl(&self) -> Option<Duration> {
+        self.ttl
+    }
 }
@@ -46,6 +46,10 @@ mod issue;
 pub mod problems;
 mod solution;
 
-pub use dashboard::{all_issues, clear, solve, submit};
-pub use issue::{Issue, IssueRecord};
+pub use dashboard::{
+    all_issues, clear, count_by_type, issue_count, issues_as_json, issues_by_severity,
+    issues_of_type, solve, solve_all_of_type, solve_and_take, solve_by_filter, submit, subscribe,
+    sweep_expired, Dashboard, DashboardEvent, DashboardSubscriber,
+};
+pub use issue::{Issue, IssueRecord, Severity};
 pub use solution::Solution;
@@ -0,0 +1,96 @@
+#[cfg(not(target_os = "windows"))]
+mod prof;
+
+use common::types::PointOffsetType;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use sparse::common::types::DimWeight;
+use sparse::index::posting_list::{PostingBuilder, PostingList, PostingListIterator};
+
+const POSTING_LIST_SIZES: [usize; 3] = [1_000, 100_000, 1_000_000];
+const SKIP_TARGETS: usize = 1_000;
+
+/// Random strictly increasing ids with random weights
+fn random_records(rnd: &mut StdRng, size: usize) -> Vec<(PointOffsetType, DimWeight)> {
+    let mut id = 0;
+    (0..size)
+        .map(|_| {
+            id += rnd.gen_range(1..10);
+            (id, rnd.gen_range(0.0..1.0))
+        })
+        .collect()
+}
+
+fn posting_list_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sparse-posting-list-group");
+    let mut rnd = StdRng::seed_from_u64(42);
+
+    for size in POSTING_LIST_SIZES {
+        let mut records = random_records(&mut rnd, size);
+        let max_id = records.last().unwrap().0;
+        // builder input arrives in random order
+        records.shuffle(&mut rnd);
+
+        group.bench_with_input(BenchmarkId::new("build", size), &records, |b, records| {
+            b.iter(|| {
+                let mut builder = PostingBuilder::new();
+                for &(id, weight) in records {
+                    builder.add(id, weight);
+                }
+                builder.build()
+            })
+        });
+
+        let posting_list: PostingList = records.into_iter().collect();
+
+        group.bench_with_input(
+            BenchmarkId::new("iterate", size),
+            &posting_list,
+            |b, posting_list| {
+                b.iter(|| {
+                    PostingListIterator::new(&posting_list.elements)
+                        .map(|element| element.weight)
+                        .sum::<DimWeight>()
+                })
+            },
+        );
+
+        let mut targets: Vec<PointOffsetType> = (0..SKIP_TARGETS)
+            .map(|_| rnd.gen_range(0..=max_id))
+            .collect();
+        targets.sort_unstable();
+
+        group.bench_with_input(
+            BenchmarkId::new("skip_to", size),
+            &posting_list,
+            |b, posting_list| {
+                b.iter(|| {
+                    let mut iter = PostingListIterator::new(&posting_list.elements);
+                    for &target in &targets {
+                        black_box(iter.skip_to(target));
+                    }
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+#[cfg(not(target_os = "windows"))]
+criterion_group! {
+    name = benches;
+    config = Criterion::default().with_profiler(prof::FlamegraphProfiler::new(100));
+    targets = posting_list_benchmark
+}
+
+#[cfg(target_os = "windows")]
+criterion_group! {
+    name = benches;
+    config = Criterion::default();
+    targets = posting_list_benchmark,
+}
+
+criterion_main!(benches);
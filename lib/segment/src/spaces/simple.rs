@@ -231,7 +231,7 @@ pub fn cosine_preprocess(vector: DenseVector) -> DenseVector {
         return vector;
     }
     length = length.sqrt();
-    vector.iter().map(|x| x / length).collect()
+    vector.into_iter().map(|x| x / length).collect()
 }
 
 pub fn dot_similarity(v1: &[VectorElementType], v2: &[VectorElementType]) -> ScoreType {
@@ -750,7 +750,22 @@ impl Default for MultiVectorConfig {
 }
 
 #[derive(Debug, Default, Deserialize, Serialize, JsonSchema, Eq, PartialEq, Copy, Clone)]
-pub struct MaxSimConfig {}
+pub struct MaxSimConfig {
+    /// How the per-token maximum similarities are aggregated into the final score
+    #[serde(default)]
+    pub pooling: MaxSimPooling,
+}
+
+/// Aggregation of the per-token maximum similarities in MaxSim
+#[derive(Debug, Default, Deserialize, Serialize, JsonSchema, Eq, PartialEq, Copy, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum MaxSimPooling {
+    /// Sum of the per-token maxima, as in ColBERT. Used by default
+    #[default]
+    Sum,
+    /// Mean of the per-token maxima
+    Mean,
+}
 
 impl VectorStorageType {
     /// Whether this storage type is a mmap on disk
@@ -3,7 +3,7 @@ use ordered_float::OrderedFloat;
 
 use crate::data_types::vectors::{MultiDenseVector, VectorElementType};
 use crate::spaces::metric::Metric;
-use crate::types::MultiVectorConfig;
+use crate::types::{MaxSimConfig, MaxSimPooling, MultiVectorConfig};
 
 pub mod custom_query_scorer;
 pub mod metric_query_scorer;
@@ -50,8 +50,41 @@ fn score_multi<TMetric: Metric<VectorElementType>>(
     multi_dense_b: &MultiDenseVector,
 ) -> ScoreType {
     match multi_vector_config {
-        MultiVectorConfig::MaxSim(_) => {
-            score_max_similarity::<TMetric>(multi_dense_a, multi_dense_b)
+        MultiVectorConfig::MaxSim(MaxSimConfig { pooling }) => {
+            let sum = score_max_similarity::<TMetric>(multi_dense_a, multi_dense_b);
+            match pooling {
+                MaxSimPooling::Sum => sum,
+                MaxSimPooling::Mean => sum / multi_dense_a.multi_vectors().count() as ScoreType,
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spaces::simple::DotProductMetric;
+
+    #[test]
+    fn test_score_multi_pooling() {
+        let query = MultiDenseVector::try_from(vec![vec![1.0, 0.0], vec![0.0, 2.0]]).unwrap();
+        let stored =
+            MultiDenseVector::try_from(vec![vec![3.0, 1.0], vec![1.0, 1.0], vec![-1.0, 4.0]])
+                .unwrap();
+
+        // per query token maxima: max(3, 1, -1) = 3 and max(2, 2, 8) = 8
+        let config = |pooling| MultiVectorConfig::MaxSim(MaxSimConfig { pooling });
+        assert_eq!(
+            score_multi::<DotProductMetric>(&config(MaxSimPooling::Sum), &query, &stored),
+            11.0
+        );
+        assert_eq!(
+            score_multi::<DotProductMetric>(&config(MaxSimPooling::Mean), &query, &stored),
+            5.5
+        );
+        assert_eq!(
+            score_multi::<DotProductMetric>(&MultiVectorConfig::default(), &query, &stored),
+            11.0
+        );
+    }
+}
@@ -1,6 +1,8 @@
+use std::collections::HashMap;
 use std::marker::PhantomData;
 
 use common::types::{PointOffsetType, ScoreType};
+use rayon::prelude::*;
 
 use super::score_multi;
 use crate::data_types::vectors::{DenseVector, MultiDenseVector, VectorElementType};
@@ -22,11 +24,17 @@ impl<'a, TMetric: Metric<VectorElementType>, TVectorStorage: MultiVectorStorage>
     MultiMetricQueryScorer<'a, TMetric, TVectorStorage>
 {
     pub fn new(query: MultiDenseVector, vector_storage: &'a TVectorStorage) -> Self {
-        let slices = query.multi_vectors();
-        let preprocessed: DenseVector = slices
-            .into_iter()
-            .flat_map(|slice| TMetric::preprocess(slice.to_vec()))
-            .collect();
+        let token_count = query.multi_vectors().count();
+        let mut preprocessed = DenseVector::with_capacity(token_count * query.dim);
+        // reuse a single buffer for all tokens, the built-in metrics preprocess it in place
+        // and hand the same allocation back
+        let mut token_buffer = DenseVector::with_capacity(query.dim);
+        for slice in query.multi_vectors() {
+            token_buffer.clear();
+            token_buffer.extend_from_slice(slice);
+            token_buffer = TMetric::preprocess(token_buffer);
+            preprocessed.extend_from_slice(&token_buffer);
+        }
         Self {
             query: MultiDenseVector::new(preprocessed, query.dim),
             vector_storage,
@@ -34,6 +42,53 @@ impl<'a, TMetric: Metric<VectorElementType>, TVectorStorage: MultiVectorStorage>
         }
     }
 
+    /// Number of vectors in the query multivector.
+    pub fn query_token_count(&self) -> usize {
+        self.query.multi_vectors().count()
+    }
+
+    /// Dimension of each vector in the query multivector.
+    pub fn query_dim(&self) -> usize {
+        self.query.dim
+    }
+
+    /// Scores the stored multivectors of `ids` against the query, writing the scores into `out`.
+    pub fn score_stored_batch(&self, ids: &[PointOffsetType], out: &mut [ScoreType]) {
+        debug_assert_eq!(ids.len(), out.len());
+        for (score, &idx) in out.iter_mut().zip(ids) {
+            *score = self.score_stored(idx);
+        }
+    }
+
+    /// Scores the stored multivectors of `ids` against the query on the rayon thread pool.
+    pub fn score_stored_parallel(&self, ids: &[PointOffsetType]) -> Vec<ScoreType>
+    where
+        Self: Sync,
+    {
+        ids.par_iter().map(|&idx| self.score_stored(idx)).collect()
+    }
+
+    /// Scores the stored multivectors of each pair in `pairs`, writing the scores into `out`.
+    /// Ids repeated across pairs are looked up once.
+    pub fn score_internal_batch(
+        &self,
+        pairs: &[(PointOffsetType, PointOffsetType)],
+        out: &mut [ScoreType],
+    ) {
+        debug_assert_eq!(pairs.len(), out.len());
+        let mut cache: HashMap<PointOffsetType, &MultiDenseVector> = HashMap::new();
+        let mut get_multi = |key| {
+            *cache
+                .entry(key)
+                .or_insert_with(|| self.vector_storage.get_multi(key))
+        };
+        for (score, &(point_a, point_b)) in out.iter_mut().zip(pairs) {
+            let multi_dense_a = get_multi(point_a);
+            let multi_dense_b = get_multi(point_b);
+            *score = self.score_multi(multi_dense_a, multi_dense_b);
+        }
+    }
+
     fn score_multi(
         &self,
         multi_dense_a: &MultiDenseVector,
@@ -4,4 +4,5 @@ mod custom_query_scorer_equivalency;
 mod test_appendable_dense_vector_storage;
 mod test_appendable_multi_dense_vector_storage;
 mod test_appendable_sparse_vector_storage;
+mod test_multi_metric_query_scorer;
 mod utils;
@@ -0,0 +1,239 @@
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use atomic_refcell::AtomicRefCell;
+use common::types::{PointOffsetType, ScoreType};
+use tempfile::Builder;
+
+use crate::common::rocksdb_wrapper::{open_db, DB_VECTOR_CF};
+use crate::data_types::vectors::{MultiDenseVector, VectorElementType};
+use crate::spaces::metric::Metric;
+use crate::spaces::simple::{CosineMetric, DotProductMetric, EuclidMetric, ManhattanMetric};
+use crate::types::{Distance, MultiVectorConfig};
+use crate::vector_storage::query_scorer::multi_metric_query_scorer::MultiMetricQueryScorer;
+use crate::vector_storage::query_scorer::{score_max_similarity, QueryScorer};
+use crate::vector_storage::simple_multi_dense_vector_storage::{
+    open_simple_multi_dense_vector_storage, SimpleMultiDenseVectorStorage,
+};
+use crate::vector_storage::{VectorStorage, VectorStorageEnum};
+
+const DIM: usize = 4;
+
+fn multi_points_fixtures() -> Vec<MultiDenseVector> {
+    (0..5)
+        .map(|i| {
+            let value = i as f32;
+            let vectors = vec![
+                vec![value, 0.0, value, value],
+                vec![value, 1.0, 0.0, -value],
+                vec![1.0, value, value, 0.0],
+            ];
+            MultiDenseVector::try_from(vectors[..=(i % 3)].to_vec()).unwrap()
+        })
+        .collect()
+}
+
+fn open_storage_with_fixtures(
+    path: &Path,
+    distance: Distance,
+    points: &[MultiDenseVector],
+) -> Arc<AtomicRefCell<VectorStorageEnum>> {
+    let db = open_db(path, &[DB_VECTOR_CF]).unwrap();
+    let storage = open_simple_multi_dense_vector_storage(
+        db,
+        DB_VECTOR_CF,
+        DIM,
+        distance,
+        MultiVectorConfig::default(),
+        &AtomicBool::new(false),
+    )
+    .unwrap();
+    {
+        let mut borrowed_storage = storage.borrow_mut();
+        for (i, vec) in points.iter().enumerate() {
+            borrowed_storage
+                .insert_vector(i as PointOffsetType, vec.into())
+                .unwrap();
+        }
+    }
+    storage
+}
+
+/// Runs `f` against a fresh simple multi dense storage holding `points`.
+fn with_multi_storage(
+    distance: Distance,
+    points: &[MultiDenseVector],
+    f: impl FnOnce(&SimpleMultiDenseVectorStorage),
+) {
+    let dir = Builder::new().prefix("storage_dir").tempdir().unwrap();
+    let storage = open_storage_with_fixtures(dir.path(), distance, points);
+    let borrowed_storage = storage.borrow();
+    let VectorStorageEnum::MultiDenseSimple(multi_storage) = &*borrowed_storage else {
+        panic!("unexpected storage type");
+    };
+    f(multi_storage);
+}
+
+fn query_fixture() -> MultiDenseVector {
+    MultiDenseVector::try_from(vec![vec![1.0, 0.0, 1.0, 0.0], vec![0.0, 2.0, 0.0, 1.0]]).unwrap()
+}
+
+#[test]
+fn test_score_stored_batch() {
+    with_multi_storage(Distance::Dot, &multi_points_fixtures(), |multi_storage| {
+        let scorer =
+            MultiMetricQueryScorer::<DotProductMetric, _>::new(query_fixture(), multi_storage);
+
+        let ids: Vec<PointOffsetType> = vec![4, 0, 2, 2, 1, 3];
+        let mut scores = vec![ScoreType::NAN; ids.len()];
+        scorer.score_stored_batch(&ids, &mut scores);
+
+        let expected: Vec<_> = ids.iter().map(|&id| scorer.score_stored(id)).collect();
+        assert_eq!(scores, expected);
+    });
+}
+
+#[test]
+fn test_score_stored_parallel() {
+    let points = multi_points_fixtures();
+    with_multi_storage(Distance::Dot, &points, |multi_storage| {
+        let scorer =
+            MultiMetricQueryScorer::<DotProductMetric, _>::new(query_fixture(), multi_storage);
+
+        let ids: Vec<PointOffsetType> = (0..1000)
+            .map(|i| (i * 7 % points.len()) as PointOffsetType)
+            .collect();
+        let expected: Vec<_> = ids.iter().map(|&id| scorer.score_stored(id)).collect();
+        assert_eq!(scorer.score_stored_parallel(&ids), expected);
+        assert!(scorer.score_stored_parallel(&[]).is_empty());
+    });
+}
+
+#[test]
+fn test_score_internal_batch() {
+    with_multi_storage(Distance::Dot, &multi_points_fixtures(), |multi_storage| {
+        let scorer =
+            MultiMetricQueryScorer::<DotProductMetric, _>::new(query_fixture(), multi_storage);
+
+        let pairs: Vec<(PointOffsetType, PointOffsetType)> =
+            vec![(0, 1), (1, 0), (2, 2), (4, 1), (3, 4), (1, 3)];
+        let mut scores = vec![ScoreType::NAN; pairs.len()];
+        scorer.score_internal_batch(&pairs, &mut scores);
+
+        let expected: Vec<_> = pairs
+            .iter()
+            .map(|&(point_a, point_b)| scorer.score_internal(point_a, point_b))
+            .collect();
+        assert_eq!(scores, expected);
+    });
+}
+
+#[test]
+fn test_query_token_count_and_dim() {
+    // the query shape doesn't depend on stored points
+    with_multi_storage(Distance::Dot, &[], |multi_storage| {
+        let query = MultiDenseVector::try_from(vec![
+            vec![1.0, 0.0, 1.0, 0.0],
+            vec![0.0, 2.0, 0.0, 1.0],
+            vec![3.0, 0.0, 0.0, 1.0],
+        ])
+        .unwrap();
+        let scorer = MultiMetricQueryScorer::<DotProductMetric, _>::new(query, multi_storage);
+        assert_eq!(scorer.query_token_count(), 3);
+        assert_eq!(scorer.query_dim(), DIM);
+    });
+}
+
+#[test]
+fn test_new_preprocesses_each_query_token() {
+    let points = multi_points_fixtures();
+    with_multi_storage(Distance::Cosine, &points, |multi_storage| {
+        let query = query_fixture();
+        let expected_query = MultiDenseVector::new(
+            query
+                .multi_vectors()
+                .flat_map(|slice| CosineMetric::preprocess(slice.to_vec()))
+                .collect(),
+            query.dim,
+        );
+
+        let scorer = MultiMetricQueryScorer::<CosineMetric, _>::new(query, multi_storage);
+        for point in &points {
+            assert_eq!(
+                scorer.score(point),
+                score_max_similarity::<CosineMetric>(&expected_query, point)
+            );
+        }
+    });
+}
+
+/// Compares `score_stored` against a sum over query tokens of the best `token_similarity`.
+fn check_max_sim_scores<TMetric: Metric<VectorElementType>>(
+    distance: Distance,
+    token_similarity: impl Fn(&[VectorElementType], &[VectorElementType]) -> ScoreType,
+) {
+    let points = multi_points_fixtures();
+    with_multi_storage(distance, &points, |multi_storage| {
+        let query = query_fixture();
+        let expected_scores: Vec<ScoreType> = points
+            .iter()
+            .map(|point| {
+                query
+                    .multi_vectors()
+                    .map(|query_token| {
+                        point
+                            .multi_vectors()
+                            .map(|point_token| token_similarity(query_token, point_token))
+                            .fold(ScoreType::NEG_INFINITY, ScoreType::max)
+                    })
+                    .sum()
+            })
+            .collect();
+
+        let scorer = MultiMetricQueryScorer::<TMetric, _>::new(query, multi_storage);
+        for (idx, expected) in expected_scores.into_iter().enumerate() {
+            let score = scorer.score_stored(idx as PointOffsetType);
+            assert!(
+                (score - expected).abs() < 1e-5,
+                "{distance:?} point {idx}: {score} != {expected}",
+            );
+        }
+    });
+}
+
+#[test]
+fn test_max_sim_dot() {
+    check_max_sim_scores::<DotProductMetric>(Distance::Dot, |a, b| {
+        a.iter().zip(b).map(|(x, y)| x * y).sum()
+    });
+}
+
+#[test]
+fn test_max_sim_cosine() {
+    // only the query is normalized by the scorer, stored vectors are expected to be preprocessed
+    check_max_sim_scores::<CosineMetric>(Distance::Cosine, |a, b| {
+        let norm = a.iter().map(|x| x * x).sum::<ScoreType>().sqrt();
+        a.iter().zip(b).map(|(x, y)| x / norm * y).sum()
+    });
+}
+
+#[test]
+fn test_max_sim_euclid() {
+    check_max_sim_scores::<EuclidMetric>(Distance::Euclid, |a, b| {
+        -a.iter()
+            .zip(b)
+            .map(|(x, y)| (x - y).powi(2))
+            .sum::<ScoreType>()
+    });
+}
+
+#[test]
+fn test_max_sim_manhattan() {
+    check_max_sim_scores::<ManhattanMetric>(Distance::Manhattan, |a, b| {
+        -a.iter()
+            .zip(b)
+            .map(|(x, y)| (x - y).abs())
+            .sum::<ScoreType>()
+    });
+}
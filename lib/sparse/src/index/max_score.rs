@@ -0,0 +1,161 @@
+use common::types::{PointOffsetType, ScoreType};
+
+use crate::common::types::DimWeight;
+use crate::index::posting_list::PostingListIterator;
+use crate::index::posting_union::PostingUnionIterator;
+use crate::index::top_k::{max_contribution, TopHeap};
+
+/// Finds the `top_k` ids with the highest sum of `weight * query_weight` over the posting lists,
+/// ordered by descending score.
+///
+/// Implements MaxScore: postings are ordered by their maximum contribution, and the longest
+/// prefix whose contributions together can't beat the current threshold is non-essential.
+/// Candidates are taken only from the essential postings, which are fully scanned, while the
+/// non-essential ones are probed with `skip_to` as long as the candidate can still enter the top.
+///
+/// Pruning relies on non-negative query weights, otherwise all ids are scored.
+pub fn search(
+    postings: Vec<(PostingListIterator<'_>, DimWeight)>,
+    top_k: usize,
+) -> Vec<(PointOffsetType, ScoreType)> {
+    let mut top = TopHeap::new(top_k);
+    if top_k == 0 {
+        return top.into_vec();
+    }
+
+    if postings.iter().any(|(_, query_weight)| *query_weight < 0.0) {
+        for (record_id, score) in PostingUnionIterator::new(postings) {
+            top.push(record_id, score);
+        }
+        return top.into_vec();
+    }
+
+    // (iterator, query weight, max contribution) ordered by ascending max contribution
+    let mut postings: Vec<_> = postings
+        .into_iter()
+        .filter_map(|(iterator, query_weight)| {
+            let max_score = max_contribution(iterator.peek()?, query_weight);
+            Some((iterator, query_weight, max_score))
+        })
+        .collect();
+    postings.sort_unstable_by(|(_, _, a), (_, _, b)| a.total_cmp(b));
+
+    // `max_score_prefix[i]` is the max contribution of the first `i` postings together
+    let max_score_prefix: Vec<ScoreType> = std::iter::once(0.0)
+        .chain(postings.iter().scan(0.0, |sum, (_, _, max_score)| {
+            *sum += max_score;
+            Some(*sum)
+        }))
+        .collect();
+
+    let mut first_essential = 0;
+    loop {
+        let threshold = top.threshold();
+        while first_essential < postings.len() && max_score_prefix[first_essential + 1] <= threshold
+        {
+            first_essential += 1;
+        }
+
+        let (non_essential, essential) = postings.split_at_mut(first_essential);
+        let Some(candidate) = essential
+            .iter()
+            .filter_map(|(iterator, _, _)| iterator.peek().map(|e| e.record_id))
+            .min()
+        else {
+            // essential postings are exhausted, or there are none left
+            break;
+        };
+
+        let mut score = 0.0;
+        for (iterator, query_weight, _) in essential.iter_mut() {
+            if iterator.peek().is_some_and(|e| e.record_id == candidate) {
+                score += iterator.next().unwrap().weight * *query_weight;
+            }
+        }
+
+        // probe non-essential postings from the most contributing one
+        for (index, (iterator, query_weight, _)) in non_essential.iter_mut().enumerate().rev() {
+            if score + max_score_prefix[index + 1] <= threshold {
+                break;
+            }
+            if let Some(element) = iterator.skip_to(candidate) {
+                score += element.weight * *query_weight;
+            }
+        }
+
+        top.push(candidate, score);
+    }
+
+    top.into_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+    use crate::index::posting_list::PostingList;
+    use crate::index::posting_list_fixture::{
+        assert_same_top, brute_force_top, iterators, random_posting_lists,
+    };
+    use crate::index::wand;
+
+    #[test]
+    fn test_max_score_matches_brute_force_and_wand() {
+        let mut rnd = StdRng::seed_from_u64(42);
+        for (terms, max_len, max_id, top_k) in [
+            (1, 100, 1_000, 10),
+            (3, 100, 200, 5),
+            (5, 1_000, 2_000, 10),
+            (8, 500, 10_000, 1),
+            (4, 50, 100, 1_000),
+        ]
+        .into_iter()
+        // many small queries to hit negative stored weights near the threshold
+        .chain(itertools::repeat_n((3, 20, 50, 1), 300))
+        {
+            let posting_lists = random_posting_lists(&mut rnd, terms, max_len, max_id);
+            let query_weights: Vec<DimWeight> =
+                (0..terms).map(|_| rnd.gen_range(0.0..2.0)).collect();
+
+            let result = search(iterators(&posting_lists, &query_weights), top_k);
+            let expected = brute_force_top(&posting_lists, &query_weights, top_k);
+            assert_same_top(&result, &expected);
+
+            let wand_result = wand::search(iterators(&posting_lists, &query_weights), top_k);
+            assert_same_top(&result, &wand_result);
+        }
+    }
+
+    #[test]
+    fn test_max_score_negative_query_weights() {
+        let mut rnd = StdRng::seed_from_u64(42);
+        let posting_lists = random_posting_lists(&mut rnd, 4, 100, 300);
+        let query_weights = [1.0, -0.5, 2.0, -1.0];
+
+        let result = search(iterators(&posting_lists, &query_weights), 10);
+        let expected = brute_force_top(&posting_lists, &query_weights, 10);
+        assert_same_top(&result, &expected);
+    }
+
+    #[test]
+    fn test_max_score_negative_stored_weights() {
+        // the negative list must not lower the bound of id 2 and make the first list non-essential
+        let posting_lists = [
+            PostingList::from(vec![(1, 1.0), (2, 1.0)]),
+            PostingList::from(vec![(1, -5.0)]),
+        ];
+        let result = search(iterators(&posting_lists, &[1.0, 1.0]), 1);
+        assert_eq!(result, vec![(2, 1.0)]);
+    }
+
+    #[test]
+    fn test_max_score_empty() {
+        let empty = PostingList::default();
+        let list = PostingList::from(vec![(1, 1.0), (2, 2.0)]);
+        assert!(search(vec![], 10).is_empty());
+        assert!(search(iterators(&[empty], &[1.0]), 10).is_empty());
+        assert!(search(iterators(&[list], &[1.0]), 0).is_empty());
+    }
+}
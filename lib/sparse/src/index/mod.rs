@@ -1,5 +1,11 @@
 #![allow(dead_code)]
 
 pub mod inverted_index;
+pub mod max_score;
 pub mod posting_list;
+#[cfg(test)]
+pub(crate) mod posting_list_fixture;
+pub mod posting_union;
 pub mod search_context;
+pub(crate) mod top_k;
+pub mod wand;
@@ -1,6 +1,8 @@
 use std::cmp::max;
+use std::mem::size_of;
 
 use common::types::PointOffsetType;
+use itertools::{EitherOrBoth, Itertools};
 use ordered_float::OrderedFloat;
 
 use crate::common::types::DimWeight;
@@ -38,11 +40,7 @@ pub struct PostingList {
 impl PostingList {
     /// used for testing
     pub fn from(records: Vec<(PointOffsetType, DimWeight)>) -> PostingList {
-        let mut posting_list = PostingBuilder::new();
-        for (id, weight) in records {
-            posting_list.add(id, weight);
-        }
-        posting_list.build()
+        records.into_iter().collect()
     }
 
     /// Creates a new posting list with a single element.
@@ -52,15 +50,98 @@ impl PostingList {
         }
     }
 
+    /// Number of elements in the posting list.
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// Checks whether the posting list contains the given `record_id`.
+    pub fn contains(&self, record_id: PointOffsetType) -> bool {
+        self.search(record_id).is_ok()
+    }
+
+    /// Returns the weight stored for the given `record_id`, if present.
+    pub fn get_weight(&self, record_id: PointOffsetType) -> Option<DimWeight> {
+        self.search(record_id)
+            .ok()
+            .map(|index| self.elements[index].weight)
+    }
+
+    /// Counts the elements with weight strictly above `threshold`.
+    /// Stops as soon as `max_next_weight` shows that no following element can exceed it.
+    pub fn count_weight_above(&self, threshold: DimWeight) -> usize {
+        let mut count = 0;
+        for element in &self.elements {
+            if element.weight > threshold {
+                count += 1;
+            } else if element.max_next_weight <= threshold {
+                break;
+            }
+        }
+        count
+    }
+
+    /// Splits the posting list into the elements with `id < threshold` and those with `id >= threshold`.
+    pub fn split_at(&self, threshold: PointOffsetType) -> (PostingList, PostingList) {
+        let mut below = PostingBuilder::new();
+        let mut above = PostingBuilder::new();
+        for element in &self.elements {
+            if element.record_id < threshold {
+                below.add(element.record_id, element.weight);
+            } else {
+                above.add(element.record_id, element.weight);
+            }
+        }
+        (below.build_from_sorted(), above.build_from_sorted())
+    }
+
+    /// Iterates over the ids present in both posting lists, yielding `(id, self_weight, other_weight)`.
+    /// Walks the shorter list and uses `skip_to` to leapfrog through the longer one.
+    pub fn intersection<'a>(
+        &'a self,
+        other: &'a PostingList,
+    ) -> impl Iterator<Item = (PointOffsetType, DimWeight, DimWeight)> + 'a {
+        let swapped = self.len() > other.len();
+        let (shorter, longer) = if swapped {
+            (other, self)
+        } else {
+            (self, other)
+        };
+        let mut longer_iter = PostingListIterator::new(&longer.elements);
+        shorter.elements.iter().filter_map(move |element| {
+            let longer_weight = longer_iter.skip_to(element.record_id)?.weight;
+            if swapped {
+                Some((element.record_id, longer_weight, element.weight))
+            } else {
+                Some((element.record_id, element.weight, longer_weight))
+            }
+        })
+    }
+
+    /// Binary-searches the elements by id.
+    /// Returns the index of the element if found, otherwise the index where it could be inserted.
+    fn search(&self, record_id: PointOffsetType) -> Result<usize, usize> {
+        self.elements
+            .binary_search_by_key(&record_id, |e| e.record_id)
+    }
+
+    /// Size of the posting elements in bytes, for memory accounting.
+    /// The mmap index lays out the elements on its own and doesn't depend on this.
+    pub fn size_in_bytes(&self) -> usize {
+        self.elements.len() * size_of::<PostingElement>()
+    }
+
     /// Upsert a posting element into the posting list.
     ///
     /// Worst case is adding a new element at the end of the list with a very large weight.
     /// This forces to propagate it as potential max_next_weight to all the previous elements.
     pub fn upsert(&mut self, posting_element: PostingElement) {
         // find insertion point in sorted posting list (most expensive operation for large posting list)
-        let index = self
-            .elements
-            .binary_search_by_key(&posting_element.record_id, |e| e.record_id);
+        let index = self.search(posting_element.record_id);
 
         let modified_index = match index {
             Ok(found_index) => {
@@ -79,13 +160,13 @@ impl PostingList {
                 // Insert new element by shifting elements to the right
                 self.elements.insert(insert_index, posting_element);
                 // the structure of the posting list is changed, need to update max_next_weight
-                if insert_index == self.elements.len() - 1 {
-                    // inserted at the end
-                    Some(insert_index)
-                } else {
-                    // inserted in the middle - need to propagated max_next_weight from the right
-                    Some(insert_index + 1)
+                if insert_index < self.elements.len() - 1 {
+                    // inserted in the middle - take max_next_weight from the right neighbour
+                    let next_element = &self.elements[insert_index + 1];
+                    let max_next_weight = next_element.max_next_weight.max(next_element.weight);
+                    self.elements[insert_index].max_next_weight = max_next_weight;
                 }
+                Some(insert_index)
             }
         };
         // Propagate max_next_weight update to the previous entries
@@ -94,6 +175,61 @@ impl PostingList {
         }
     }
 
+    /// Removes the element with the given `record_id` from the posting list.
+    /// Returns true if the element was present.
+    pub fn remove(&mut self, record_id: PointOffsetType) -> bool {
+        let Ok(index) = self.search(record_id) else {
+            return false;
+        };
+        let removed = self.elements.remove(index);
+        // previous entries only depend on the removed weight if it was the max of the tail
+        if removed.weight > removed.max_next_weight {
+            self.recompute_max_next_weight_to_the_left(index);
+        }
+        true
+    }
+
+    /// Merges two posting lists into a new one containing the sorted union of their ids.
+    /// Weights of ids present in both lists are combined with `combine(self_weight, other_weight)`.
+    pub fn merge(
+        &self,
+        other: &PostingList,
+        combine: impl Fn(DimWeight, DimWeight) -> DimWeight,
+    ) -> PostingList {
+        let mut builder = PostingBuilder::new();
+        for pair in self
+            .elements
+            .iter()
+            .merge_join_by(&other.elements, |a, b| a.record_id.cmp(&b.record_id))
+        {
+            match pair {
+                EitherOrBoth::Both(a, b) => builder.add(a.record_id, combine(a.weight, b.weight)),
+                EitherOrBoth::Left(e) | EitherOrBoth::Right(e) => {
+                    builder.add(e.record_id, e.weight)
+                }
+            }
+        }
+        builder.build_from_sorted()
+    }
+
+    /// Recomputes `max_next_weight` of the entries before `up_to_index` after the tail got smaller.
+    /// Stops as soon as an entry already holds the expected value.
+    fn recompute_max_next_weight_to_the_left(&mut self, up_to_index: usize) {
+        let mut max_next_weight = self
+            .elements
+            .get(up_to_index)
+            .map_or(DEFAULT_MAX_NEXT_WEIGHT, |e| e.max_next_weight.max(e.weight));
+
+        for element in self.elements[..up_to_index].iter_mut().rev() {
+            if element.max_next_weight == max_next_weight {
+                // previous entries are already consistent
+                break;
+            }
+            element.max_next_weight = max_next_weight;
+            max_next_weight = max_next_weight.max(element.weight);
+        }
+    }
+
     /// Propagates `max_next_weight` from the entry at `up_to_index` to previous entries.
     /// If an entry has a weight larger than `max_next_weight`, the propagation stops.
     fn propagate_max_next_weight_to_the_left(&mut self, up_to_index: usize) {
@@ -120,6 +256,30 @@ impl PostingList {
     }
 }
 
+/// How [`PostingBuilder`] resolves records added more than once with the same id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    /// Panic on the first duplicate id
+    Panic,
+    /// Keep the weight of the record added last
+    KeepLast,
+    /// Keep the largest weight
+    KeepMax,
+    /// Sum the weights of all records with the same id
+    Sum,
+}
+
+impl Default for DuplicatePolicy {
+    /// Duplicates are a bug in debug builds, release builds keep the last record.
+    fn default() -> Self {
+        if cfg!(debug_assertions) {
+            DuplicatePolicy::Panic
+        } else {
+            DuplicatePolicy::KeepLast
+        }
+    }
+}
+
 pub struct PostingBuilder {
     elements: Vec<PostingElement>,
 }
@@ -143,19 +303,52 @@ impl PostingBuilder {
     }
 
     /// Consume the builder and return the posting list.
-    pub fn build(mut self) -> PostingList {
-        // Sort by id
-        self.elements.sort_unstable_by_key(|e| e.record_id);
+    pub fn build(self) -> PostingList {
+        self.build_with(DuplicatePolicy::default())
+    }
+
+    /// Consume the builder and return the posting list, resolving duplicate ids with `on_duplicate`.
+    pub fn build_with(mut self, on_duplicate: DuplicatePolicy) -> PostingList {
+        // Sort by id, stable to keep the insertion order of duplicates
+        self.elements.sort_by_key(|e| e.record_id);
+
+        // `later` is removed when the closure returns true, `kept` is the earlier element
+        self.elements.dedup_by(|later, kept| {
+            if later.record_id != kept.record_id {
+                return false;
+            }
+            match on_duplicate {
+                DuplicatePolicy::Panic => {
+                    panic!("Duplicate id {} in posting list", kept.record_id)
+                }
+                DuplicatePolicy::KeepLast => kept.weight = later.weight,
+                DuplicatePolicy::KeepMax => kept.weight = kept.weight.max(later.weight),
+                DuplicatePolicy::Sum => kept.weight += later.weight,
+            }
+            true
+        });
+
+        self.build_from_sorted()
+    }
 
-        // Check for duplicates
+    /// Consume the builder and return the posting list, skipping the sort.
+    /// Records must have been added in strictly increasing id order.
+    pub fn build_from_sorted(mut self) -> PostingList {
+        // Check for duplicates and ordering
         #[cfg(debug_assertions)]
         {
             if let Some(e) = self
                 .elements
                 .windows(2)
-                .find(|e| e[0].record_id == e[1].record_id)
+                .find(|e| e[0].record_id >= e[1].record_id)
             {
-                panic!("Duplicate id {} in posting list", e[0].record_id);
+                if e[0].record_id == e[1].record_id {
+                    panic!("Duplicate id {} in posting list", e[0].record_id);
+                }
+                panic!(
+                    "Unsorted ids {} and {} in posting list",
+                    e[0].record_id, e[1].record_id
+                );
             }
         }
 
@@ -172,10 +365,28 @@ impl PostingBuilder {
     }
 }
 
+impl Extend<(PointOffsetType, DimWeight)> for PostingBuilder {
+    fn extend<T: IntoIterator<Item = (PointOffsetType, DimWeight)>>(&mut self, iter: T) {
+        for (record_id, weight) in iter {
+            self.add(record_id, weight);
+        }
+    }
+}
+
+impl FromIterator<(PointOffsetType, DimWeight)> for PostingList {
+    fn from_iter<T: IntoIterator<Item = (PointOffsetType, DimWeight)>>(iter: T) -> Self {
+        let mut builder = PostingBuilder::new();
+        builder.extend(iter);
+        builder.build()
+    }
+}
+
 /// Iterator over posting list elements offering skipping abilities to avoid full iteration.
 pub struct PostingListIterator<'a> {
     pub elements: &'a [PostingElement],
     pub current_index: usize,
+    /// Exclusive end of the unconsumed elements, moved by iterating from the back.
+    end: usize,
 }
 
 impl<'a> PostingListIterator<'a> {
@@ -183,34 +394,57 @@ impl<'a> PostingListIterator<'a> {
         PostingListIterator {
             elements,
             current_index: 0,
+            end: elements.len(),
         }
     }
 
     /// Slice of the remaining elements.
     pub fn remaining_elements(&self) -> &'a [PostingElement] {
-        &self.elements[self.current_index..]
+        &self.elements[self.current_index..self.end]
     }
 
     /// Advances the iterator to the next element.
     pub fn advance(&mut self) {
-        if self.current_index < self.elements.len() {
+        if self.current_index < self.end {
             self.current_index += 1;
         }
     }
 
     /// Advances the iterator by `count` elements.
     pub fn advance_by(&mut self, count: usize) {
-        self.current_index = (self.current_index + count).min(self.elements.len());
+        self.current_index = self.current_index.saturating_add(count).min(self.end);
+    }
+
+    /// Returns the number of elements already consumed from the front.
+    pub fn position(&self) -> usize {
+        self.current_index
+    }
+
+    /// Moves the iterator to the absolute `index`, clamped to the end of the remaining elements.
+    pub fn seek(&mut self, index: usize) {
+        self.current_index = index.min(self.end);
+    }
+
+    /// Returns the iterator to its initial state, including elements consumed from the back.
+    pub fn reset(&mut self) {
+        self.current_index = 0;
+        self.end = self.elements.len();
     }
 
     /// Returns the next element without advancing the iterator.
     pub fn peek(&self) -> Option<&PostingElement> {
-        self.elements.get(self.current_index)
+        self.remaining_elements().first()
+    }
+
+    /// Returns the element `n` positions ahead of the next one without advancing the iterator.
+    /// `peek_n(0)` is the same as `peek()`.
+    pub fn peek_n(&self, n: usize) -> Option<&PostingElement> {
+        self.remaining_elements().get(n)
     }
 
     /// Returns the number of elements from the current position to the end of the list.
     pub fn len_to_end(&self) -> usize {
-        self.elements.len() - self.current_index
+        self.end - self.current_index
     }
 
     /// Tries to find the element with ID == id and returns it.
@@ -221,13 +455,14 @@ impl<'a> PostingListIterator<'a> {
     /// Uses binary search.
     pub fn skip_to(&mut self, id: PointOffsetType) -> Option<&PostingElement> {
         // Check if we are already at the end
-        if self.current_index >= self.elements.len() {
+        if self.current_index >= self.end {
             return None;
         }
 
         // Use binary search to find the next element with ID > id
-        let next_element =
-            self.elements[self.current_index..].binary_search_by(|e| e.record_id.cmp(&id));
+        let next_element = self
+            .remaining_elements()
+            .binary_search_by(|e| e.record_id.cmp(&id));
 
         match next_element {
             Ok(found_offset) => {
@@ -241,13 +476,116 @@ impl<'a> PostingListIterator<'a> {
         }
     }
 
+    /// Looks up a sorted list of `targets`, advancing the iterator monotonically through them.
+    /// Clears `out` and fills it with the weight of each target, or `None` if it is absent.
+    /// Uses exponential search from the current position, so close targets are cheap to reach.
+    pub fn skip_to_many(&mut self, targets: &[PointOffsetType], out: &mut Vec<Option<DimWeight>>) {
+        debug_assert!(targets.windows(2).all(|w| w[0] <= w[1]));
+        out.clear();
+        out.extend(
+            targets
+                .iter()
+                .map(|&target| self.gallop_to(target).map(|e| e.weight)),
+        );
+    }
+
+    /// Same as `skip_to`, but probes exponentially growing offsets before the binary search.
+    fn gallop_to(&mut self, id: PointOffsetType) -> Option<&PostingElement> {
+        let remaining = self.remaining_elements();
+
+        let mut bound = 1;
+        while bound < remaining.len() && remaining[bound].record_id < id {
+            bound *= 2;
+        }
+        let search_start = bound / 2;
+        let search_end = (bound + 1).min(remaining.len());
+
+        match remaining[search_start..search_end].binary_search_by_key(&id, |e| e.record_id) {
+            Ok(found_offset) => {
+                self.current_index += search_start + found_offset;
+                Some(&self.elements[self.current_index])
+            }
+            Err(insert_offset) => {
+                self.current_index += search_start + insert_offset;
+                None
+            }
+        }
+    }
+
     /// Skips to the end of the posting list and returns None.
     pub fn skip_to_end(&mut self) -> Option<&PostingElement> {
-        self.current_index = self.elements.len();
+        self.current_index = self.end;
         None
     }
+
+    /// Yields only the remaining elements with weight strictly above `threshold`.
+    /// Stops as soon as `max_next_weight` shows that no following element can exceed it.
+    pub fn filter_weight(
+        mut self,
+        threshold: DimWeight,
+    ) -> impl Iterator<Item = &'a PostingElement> {
+        std::iter::from_fn(move || loop {
+            let element = self.next()?;
+            if element.max_next_weight <= threshold {
+                self.skip_to_end();
+            }
+            if element.weight > threshold {
+                return Some(element);
+            }
+        })
+    }
+}
+
+impl<'a> Iterator for PostingListIterator<'a> {
+    type Item = &'a PostingElement;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.current_index >= self.end {
+            return None;
+        }
+        let element = &self.elements[self.current_index];
+        self.current_index += 1;
+        Some(element)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len_to_end();
+        (len, Some(len))
+    }
+
+    /// Jumps directly to the n-th next element instead of visiting the skipped ones.
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.advance_by(n);
+        self.next()
+    }
+
+    fn count(self) -> usize {
+        self.len_to_end()
+    }
+
+    /// Folds over the remaining slice directly, without going through `next` per element.
+    fn fold<B, F>(self, init: B, f: F) -> B
+    where
+        F: FnMut(B, Self::Item) -> B,
+    {
+        self.remaining_elements().iter().fold(init, f)
+    }
 }
 
+/// Elements consumed from the back are excluded from the remaining ones, `elements` keeps the
+/// whole list.
+impl<'a> DoubleEndedIterator for PostingListIterator<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.current_index >= self.end {
+            return None;
+        }
+        self.end -= 1;
+        Some(&self.elements[self.end])
+    }
+}
+
+impl<'a> ExactSizeIterator for PostingListIterator<'a> {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -288,6 +626,258 @@ mod tests {
         assert!(iter.peek().is_none());
     }
 
+    #[test]
+    fn test_build_from_sorted() {
+        let records: Vec<_> = (0..50).map(|i| (i * 2, (i % 7) as DimWeight)).collect();
+
+        let mut sorted_builder = PostingBuilder::new();
+        let mut builder = PostingBuilder::new();
+        for &(id, weight) in &records {
+            sorted_builder.add(id, weight);
+        }
+        for &(id, weight) in records.iter().rev() {
+            builder.add(id, weight);
+        }
+
+        assert_eq!(sorted_builder.build_from_sorted(), builder.build());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Unsorted ids")]
+    fn test_build_from_sorted_unsorted_input() {
+        let mut builder = PostingBuilder::new();
+        builder.add(2, 1.0);
+        builder.add(1, 1.0);
+        builder.build_from_sorted();
+    }
+
+    #[test]
+    fn test_build_with_duplicate_policy() {
+        let build = |policy| {
+            let mut builder = PostingBuilder::new();
+            builder.add(3, 1.0);
+            builder.add(1, 2.0);
+            builder.add(3, 4.0);
+            builder.add(2, 1.0);
+            builder.add(3, 2.0);
+            builder
+                .build_with(policy)
+                .elements
+                .iter()
+                .map(|e| (e.record_id, e.weight))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            build(DuplicatePolicy::KeepLast),
+            vec![(1, 2.0), (2, 1.0), (3, 2.0)]
+        );
+        assert_eq!(
+            build(DuplicatePolicy::KeepMax),
+            vec![(1, 2.0), (2, 1.0), (3, 4.0)]
+        );
+        assert_eq!(
+            build(DuplicatePolicy::Sum),
+            vec![(1, 2.0), (2, 1.0), (3, 7.0)]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Duplicate id 3")]
+    fn test_build_with_duplicate_policy_panic() {
+        let mut builder = PostingBuilder::new();
+        builder.add(3, 1.0);
+        builder.add(3, 2.0);
+        builder.build_with(DuplicatePolicy::Panic);
+    }
+
+    #[test]
+    fn test_skip_to_many() {
+        let posting_list = PostingList::from(
+            (0..200)
+                .map(|i| (i * 3, i as DimWeight))
+                .collect::<Vec<_>>(),
+        );
+        let targets: Vec<PointOffsetType> =
+            vec![0, 1, 3, 4, 30, 31, 33, 300, 301, 450, 597, 598, 1000];
+
+        let mut iter = PostingListIterator::new(&posting_list.elements);
+        let mut out = vec![Some(1.0)];
+        iter.skip_to_many(&targets, &mut out);
+
+        let mut single_iter = PostingListIterator::new(&posting_list.elements);
+        let expected: Vec<_> = targets
+            .iter()
+            .map(|&target| single_iter.skip_to(target).map(|e| e.weight))
+            .collect();
+
+        assert_eq!(out, expected);
+        assert_eq!(out[..3], [Some(0.0), None, Some(1.0)]);
+        assert_eq!(iter.current_index, single_iter.current_index);
+        assert!(iter.peek().is_none());
+
+        // resume after a partial batch
+        let mut iter = PostingListIterator::new(&posting_list.elements);
+        iter.skip_to_many(&[10, 12], &mut out);
+        assert_eq!(out, vec![None, Some(4.0)]);
+        assert_eq!(iter.peek().unwrap().record_id, 12);
+        iter.skip_to_many(&[], &mut out);
+        assert!(out.is_empty());
+        assert_eq!(iter.peek().unwrap().record_id, 12);
+    }
+
+    #[test]
+    fn test_from_iterator_and_extend() {
+        let records = [(5, 1.0), (1, 2.0), (3, 3.0), (7, 0.5)];
+
+        let mut builder = PostingBuilder::new();
+        for (id, weight) in records {
+            builder.add(id, weight);
+        }
+        let expected = builder.build();
+
+        let collected: PostingList = records.into_iter().collect();
+        assert_eq!(collected, expected);
+
+        let mut builder = PostingBuilder::new();
+        builder.add(5, 1.0);
+        builder.extend(records[1..].iter().copied());
+        assert_eq!(builder.build(), expected);
+    }
+
+    #[test]
+    fn test_iterator_nth() {
+        let posting_list = PostingList::from((0..50).map(|i| (i * 2, i as DimWeight)).collect());
+
+        for n in [0, 1, 7, 48, 49, 50, 100] {
+            let mut iter = PostingListIterator::new(&posting_list.elements);
+            let mut expected_iter = posting_list.elements.iter();
+            assert_eq!(iter.nth(n), expected_iter.nth(n));
+            assert_eq!(iter.next(), expected_iter.next());
+            assert_eq!(iter.len_to_end(), expected_iter.len());
+        }
+
+        // relative to the current position
+        let mut iter = PostingListIterator::new(&posting_list.elements);
+        iter.skip_to(20);
+        assert_eq!(iter.nth(2).unwrap().record_id, 24);
+
+        // a huge `n` exhausts the iterator instead of wrapping around
+        assert_eq!(iter.nth(usize::MAX), None);
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.len_to_end(), 0);
+    }
+
+    #[test]
+    fn test_iterator_count_and_fold() {
+        let posting_list = PostingList::from((0..50).map(|i| (i * 2, i as DimWeight)).collect());
+
+        for skip in [0, 1, 25, 49, 50] {
+            let make_iter = || {
+                let mut iter = PostingListIterator::new(&posting_list.elements);
+                iter.advance_by(skip);
+                iter.next_back();
+                iter
+            };
+
+            // manual walk through `next`
+            let mut manual_count = 0;
+            let mut manual_sum = 0.0;
+            for element in make_iter() {
+                manual_count += 1;
+                manual_sum += element.weight;
+            }
+
+            assert_eq!(make_iter().count(), manual_count);
+            let folded = make_iter().fold(0.0, |sum, element| sum + element.weight);
+            assert_eq!(folded, manual_sum);
+        }
+    }
+
+    #[test]
+    fn test_peek_n() {
+        let posting_list = PostingList::from((0..10).map(|i| (i * 2, i as DimWeight)).collect());
+        let mut iter = PostingListIterator::new(&posting_list.elements);
+
+        assert_eq!(iter.peek_n(0), iter.peek());
+        assert_eq!(iter.peek_n(3).unwrap().record_id, 6);
+        assert_eq!(iter.peek_n(9).unwrap().record_id, 18);
+        assert_eq!(iter.peek_n(10), None);
+        assert_eq!(iter.peek_n(usize::MAX), None);
+
+        // lookahead doesn't consume anything
+        assert_eq!(iter.next().unwrap().record_id, 0);
+
+        iter.skip_to(12);
+        assert_eq!(iter.peek_n(0), iter.peek());
+        assert_eq!(iter.peek_n(2).unwrap().record_id, 16);
+        assert_eq!(iter.peek_n(4), None);
+        assert_eq!(iter.next().unwrap().record_id, 12);
+    }
+
+    #[test]
+    fn test_filter_weight() {
+        let weights = [1.0, 5.0, 2.0, 0.5, 3.0, 3.0, 0.0, 1.5];
+        let posting_list: PostingList = weights
+            .iter()
+            .enumerate()
+            .map(|(id, &weight)| (id as PointOffsetType, weight))
+            .collect();
+
+        for threshold in [-1.0, 0.0, 0.5, 1.0, 2.5, 3.0, 4.9, 5.0, 10.0] {
+            let filtered: Vec<_> = PostingListIterator::new(&posting_list.elements)
+                .filter_weight(threshold)
+                .collect();
+            let expected: Vec<_> = posting_list
+                .elements
+                .iter()
+                .filter(|element| element.weight > threshold)
+                .collect();
+            assert_eq!(filtered, expected);
+        }
+
+        // elements behind a bound that is below the threshold are never visited
+        let elements = [
+            PostingElement {
+                record_id: 1,
+                weight: 3.0,
+                max_next_weight: 1.0,
+            },
+            PostingElement {
+                record_id: 2,
+                weight: 5.0,
+                max_next_weight: f32::NEG_INFINITY,
+            },
+        ];
+        let filtered: Vec<_> = PostingListIterator::new(&elements)
+            .filter_weight(2.0)
+            .map(|element| element.record_id)
+            .collect();
+        assert_eq!(filtered, vec![1]);
+    }
+
+    #[test]
+    fn test_position_and_seek() {
+        let posting_list = PostingList::from((0..10).map(|i| (i * 5, i as DimWeight)).collect());
+        let mut iter = PostingListIterator::new(&posting_list.elements);
+        assert_eq!(iter.position(), 0);
+
+        iter.skip_to(20);
+        assert_eq!(iter.position(), 4);
+        assert_eq!(iter.position() + iter.len_to_end(), posting_list.len());
+
+        for k in [7, 0, 9, 3] {
+            iter.seek(k);
+            assert_eq!(iter.position(), k);
+            assert_eq!(iter.next(), Some(&posting_list.elements[k]));
+        }
+
+        iter.seek(100);
+        assert_eq!(iter.position(), posting_list.len());
+        assert!(iter.next().is_none());
+    }
+
     #[test]
     fn test_upsert_insert_last() {
         let mut builder = PostingBuilder::new();
@@ -380,6 +970,306 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_upsert_insert_interior_and_first() {
+        let mut posting_list = PostingList::from(vec![(2, 2.0), (5, 5.0), (9, 1.0), (12, 3.0)]);
+
+        // insert before the first element and into several gaps
+        posting_list.upsert(PostingElement::new(1, 0.5));
+        posting_list.upsert(PostingElement::new(7, 6.0));
+        posting_list.upsert(PostingElement::new(3, 1.5));
+        posting_list.upsert(PostingElement::new(10, 2.5));
+
+        let expected = [
+            (1, 0.5),
+            (2, 2.0),
+            (3, 1.5),
+            (5, 5.0),
+            (7, 6.0),
+            (9, 1.0),
+            (10, 2.5),
+            (12, 3.0),
+        ];
+        assert_eq!(posting_list.elements.len(), expected.len());
+        for (element, (id, weight)) in posting_list.elements.iter().zip(expected) {
+            assert_eq!(element.record_id, id);
+            assert_eq!(element.weight, weight);
+        }
+
+        // strictly sorted and max_next_weight matches the weights on the right
+        for (i, element) in posting_list.elements.iter().enumerate() {
+            let max_right = posting_list.elements[i + 1..]
+                .iter()
+                .map(|e| e.weight)
+                .fold(DEFAULT_MAX_NEXT_WEIGHT, f32::max);
+            assert_eq!(element.max_next_weight, max_right);
+        }
+        assert!(posting_list
+            .elements
+            .windows(2)
+            .all(|w| w[0].record_id < w[1].record_id));
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut posting_list =
+            PostingList::from(vec![(1, 1.0), (2, 5.0), (3, 9.0), (4, 2.0), (5, 3.0)]);
+
+        // missing id
+        assert!(!posting_list.remove(6));
+        assert_eq!(posting_list.elements.len(), 5);
+
+        // middle element holding the max weight of the tail
+        assert!(posting_list.remove(3));
+        assert!(!posting_list.remove(3));
+        let ids: Vec<_> = posting_list.elements.iter().map(|e| e.record_id).collect();
+        assert_eq!(ids, vec![1, 2, 4, 5]);
+        assert_eq!(posting_list.elements[0].max_next_weight, 5.0);
+        assert_eq!(posting_list.elements[1].max_next_weight, 3.0);
+
+        // first element
+        assert!(posting_list.remove(1));
+        assert_eq!(posting_list.elements[0].record_id, 2);
+        assert_eq!(posting_list.elements.len(), 3);
+
+        // last element
+        assert!(posting_list.remove(5));
+        let ids: Vec<_> = posting_list.elements.iter().map(|e| e.record_id).collect();
+        assert_eq!(ids, vec![2, 4]);
+        assert_eq!(posting_list.elements[0].max_next_weight, 2.0);
+        assert_eq!(
+            posting_list.elements[1].max_next_weight,
+            DEFAULT_MAX_NEXT_WEIGHT
+        );
+    }
+
+    #[test]
+    fn test_merge() {
+        let ids_and_weights = |list: &PostingList| -> Vec<(PointOffsetType, DimWeight)> {
+            list.elements
+                .iter()
+                .map(|e| (e.record_id, e.weight))
+                .collect()
+        };
+
+        // disjoint
+        let left = PostingList::from(vec![(1, 1.0), (3, 3.0)]);
+        let right = PostingList::from(vec![(2, 2.0), (4, 4.0)]);
+        let merged = left.merge(&right, f32::max);
+        assert_eq!(
+            ids_and_weights(&merged),
+            vec![(1, 1.0), (2, 2.0), (3, 3.0), (4, 4.0)]
+        );
+        assert_eq!(merged.elements[0].max_next_weight, 4.0);
+
+        // overlapping
+        let left = PostingList::from(vec![(1, 1.0), (2, 2.0), (5, 5.0)]);
+        let right = PostingList::from(vec![(2, 3.0), (4, 4.0), (5, 1.0)]);
+        let merged = left.merge(&right, |a, b| a + b);
+        assert_eq!(
+            ids_and_weights(&merged),
+            vec![(1, 1.0), (2, 5.0), (4, 4.0), (5, 6.0)]
+        );
+
+        // fully duplicate
+        let left = PostingList::from(vec![(1, 1.0), (2, 2.0)]);
+        let right = PostingList::from(vec![(1, 3.0), (2, 0.5)]);
+        let merged = left.merge(&right, f32::max);
+        assert_eq!(ids_and_weights(&merged), vec![(1, 3.0), (2, 2.0)]);
+
+        // with empty
+        let merged = left.merge(&PostingList::default(), f32::max);
+        assert_eq!(merged, left);
+    }
+
+    #[test]
+    fn test_iterator_double_ended() {
+        for size in [0, 1, 2, 7, 100] {
+            let posting_list = PostingList::from(
+                (0..size)
+                    .map(|i| (i * 3 + 1, i as DimWeight))
+                    .collect::<Vec<_>>(),
+            );
+
+            let forward: Vec<_> = PostingListIterator::new(&posting_list.elements).collect();
+            let mut backward: Vec<_> = PostingListIterator::new(&posting_list.elements)
+                .rev()
+                .collect();
+            backward.reverse();
+            assert_eq!(forward.len(), size as usize);
+            assert_eq!(forward, backward);
+        }
+
+        // consume from both ends
+        let posting_list = PostingList::from(vec![(1, 1.0), (2, 2.0), (3, 3.0), (4, 4.0)]);
+        let mut iter = PostingListIterator::new(&posting_list.elements);
+        assert_eq!(iter.next().unwrap().record_id, 1);
+        assert_eq!(iter.next_back().unwrap().record_id, 4);
+        assert_eq!(iter.len(), 2);
+        assert_eq!(iter.len_to_end(), 2);
+        assert_eq!(iter.next_back().unwrap().record_id, 3);
+        assert_eq!(iter.next().unwrap().record_id, 2);
+        assert!(iter.next().is_none());
+        assert!(iter.next_back().is_none());
+        assert!(iter.peek().is_none());
+
+        // back iteration leaves the underlying list intact
+        let mut iter = PostingListIterator::new(&posting_list.elements);
+        assert_eq!(iter.next_back().unwrap().record_id, 4);
+        assert_eq!(iter.elements, posting_list.elements.as_slice());
+        assert_eq!(iter.position() + iter.len_to_end(), 3);
+        assert_eq!(iter.skip_to(4), None);
+        assert_eq!(iter.len_to_end(), 0);
+        iter.seek(10);
+        assert_eq!(iter.position(), 3);
+        iter.seek(1);
+        assert_eq!(iter.peek_n(1).unwrap().record_id, 3);
+        assert_eq!(iter.peek_n(2), None);
+    }
+
+    #[test]
+    fn test_iterator_reset() {
+        let posting_list = PostingList::from((0..10).map(|i| (i * 2, i as DimWeight)).collect());
+        let fresh: Vec<_> = PostingListIterator::new(&posting_list.elements).collect();
+
+        let mut iter = PostingListIterator::new(&posting_list.elements);
+        iter.next();
+        iter.next_back();
+        iter.skip_to(7);
+        iter.reset();
+        assert_eq!(iter.position(), 0);
+        assert_eq!(iter.len_to_end(), fresh.len());
+        assert_eq!(iter.collect::<Vec<_>>(), fresh);
+    }
+
+    #[test]
+    fn test_len_and_is_empty() {
+        let empty = PostingList::default();
+        assert!(empty.is_empty());
+        assert_eq!(empty.len(), 0);
+
+        let one = PostingList::new_one(1, 1.0);
+        assert!(!one.is_empty());
+        assert_eq!(one.len(), 1);
+
+        let mut posting_list = PostingList::from((0..10).map(|i| (i, 1.0)).collect());
+        assert_eq!(posting_list.len(), 10);
+        assert_eq!(
+            posting_list.len(),
+            PostingListIterator::new(&posting_list.elements).count()
+        );
+
+        posting_list.upsert(PostingElement::new(20, 1.0));
+        posting_list.remove(0);
+        assert_eq!(posting_list.len(), 10);
+    }
+
+    #[test]
+    fn test_contains() {
+        let posting_list = PostingList::from(vec![(2, 1.0), (4, 1.0), (5, 1.0), (10, 1.0)]);
+
+        for id in [2, 4, 5, 10] {
+            assert!(posting_list.contains(id));
+        }
+        // below, between and above the stored ids
+        for id in [0, 1, 3, 6, 9, 11, PointOffsetType::MAX] {
+            assert!(!posting_list.contains(id));
+        }
+        assert!(!PostingList::default().contains(0));
+    }
+
+    #[test]
+    fn test_get_weight() {
+        let records: Vec<_> = (0..20).map(|i| (i * 3, i as DimWeight / 2.0)).collect();
+        let posting_list = PostingList::from(records.clone());
+
+        for (id, weight) in records {
+            assert_eq!(posting_list.get_weight(id), Some(weight));
+            assert_eq!(posting_list.get_weight(id + 1), None);
+        }
+        assert_eq!(posting_list.get_weight(PointOffsetType::MAX), None);
+    }
+
+    #[test]
+    fn test_count_weight_above() {
+        let weights = [1.0, 5.0, 2.0, 0.5, 3.0, 3.0, 0.0, 1.5];
+        let posting_list: PostingList = weights
+            .iter()
+            .enumerate()
+            .map(|(id, &weight)| (id as PointOffsetType, weight))
+            .collect();
+
+        for threshold in [-1.0, 0.0, 0.5, 1.0, 2.5, 3.0, 4.9, 5.0, 10.0] {
+            let expected = PostingListIterator::new(&posting_list.elements)
+                .filter(|element| element.weight > threshold)
+                .count();
+            assert_eq!(posting_list.count_weight_above(threshold), expected);
+        }
+        assert_eq!(PostingList::default().count_weight_above(0.0), 0);
+    }
+
+    #[test]
+    fn test_split_at() {
+        let posting_list = PostingList::from(vec![(2, 4.0), (4, 1.0), (6, 3.0), (8, 2.0)]);
+
+        let (below, above) = posting_list.split_at(0);
+        assert!(below.is_empty());
+        assert_eq!(above, posting_list);
+
+        let (below, above) = posting_list.split_at(100);
+        assert_eq!(below, posting_list);
+        assert!(above.is_empty());
+
+        let (below, above) = posting_list.split_at(5);
+        assert_eq!(below, PostingList::from(vec![(2, 4.0), (4, 1.0)]));
+        assert_eq!(above, PostingList::from(vec![(6, 3.0), (8, 2.0)]));
+        // max_next_weight is computed per half
+        assert_eq!(below.elements[0].max_next_weight, 1.0);
+
+        // threshold equal to an existing id goes to the upper half
+        let (below, above) = posting_list.split_at(6);
+        assert_eq!(below.len(), 2);
+        assert_eq!(above.elements[0].record_id, 6);
+    }
+
+    #[test]
+    fn test_intersection() {
+        let left = PostingList::from(vec![(1, 1.0), (3, 3.0), (5, 5.0)]);
+        let right = PostingList::from(vec![(2, 2.0), (4, 4.0), (6, 6.0)]);
+        assert_eq!(left.intersection(&right).count(), 0);
+
+        // partial overlap, the longer list on either side
+        let longer = PostingList::from((0..20).map(|i| (i, i as DimWeight)).collect());
+        let shorter = PostingList::from(vec![(3, 0.5), (7, 1.5), (25, 2.5)]);
+        assert_eq!(
+            shorter.intersection(&longer).collect::<Vec<_>>(),
+            vec![(3, 0.5, 3.0), (7, 1.5, 7.0)]
+        );
+        assert_eq!(
+            longer.intersection(&shorter).collect::<Vec<_>>(),
+            vec![(3, 3.0, 0.5), (7, 7.0, 1.5)]
+        );
+
+        // identical
+        let expected: Vec<_> = longer
+            .elements
+            .iter()
+            .map(|e| (e.record_id, e.weight, e.weight))
+            .collect();
+        assert_eq!(longer.intersection(&longer).collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn test_size_in_bytes() {
+        assert_eq!(PostingList::default().size_in_bytes(), 0);
+
+        let small = PostingList::from(vec![(1, 1.0), (2, 2.0)]);
+        let large = PostingList::from((0..100).map(|i| (i, 1.0)).collect());
+        assert!(small.size_in_bytes() > 0);
+        assert!(large.size_in_bytes() > small.size_in_bytes());
+        assert_eq!(large.size_in_bytes(), 100 * size_of::<PostingElement>());
+    }
+
     #[test]
     fn test_upsert_update() {
         let mut builder = PostingBuilder::new();
@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+
+use common::types::{PointOffsetType, ScoreType};
+use rand::Rng;
+
+use crate::common::types::DimWeight;
+use crate::index::posting_list::{PostingList, PostingListIterator};
+
+pub fn random_posting_lists(
+    rnd: &mut impl Rng,
+    count: usize,
+    max_len: usize,
+    max_id: PointOffsetType,
+) -> Vec<PostingList> {
+    (0..count)
+        .map(|_| {
+            let len = rnd.gen_range(0..=max_len);
+            (0..len)
+                .map(|_| (rnd.gen_range(0..max_id), rnd.gen_range(-10.0..10.0)))
+                .collect::<HashMap<_, _>>()
+                .into_iter()
+                .collect()
+        })
+        .collect()
+}
+
+pub fn brute_force_top(
+    posting_lists: &[PostingList],
+    query_weights: &[DimWeight],
+    top_k: usize,
+) -> Vec<(PointOffsetType, ScoreType)> {
+    let mut scores: HashMap<PointOffsetType, ScoreType> = HashMap::new();
+    for (list, query_weight) in posting_lists.iter().zip(query_weights) {
+        for element in &list.elements {
+            *scores.entry(element.record_id).or_default() += element.weight * query_weight;
+        }
+    }
+    let mut scores: Vec<_> = scores.into_iter().collect();
+    scores.sort_unstable_by(|(_, a), (_, b)| b.total_cmp(a));
+    scores.truncate(top_k);
+    scores
+}
+
+pub fn iterators<'a>(
+    posting_lists: &'a [PostingList],
+    query_weights: &[DimWeight],
+) -> Vec<(PostingListIterator<'a>, DimWeight)> {
+    posting_lists
+        .iter()
+        .zip(query_weights)
+        .map(|(list, &weight)| (PostingListIterator::new(&list.elements), weight))
+        .collect()
+}
+
+pub fn assert_same_top(
+    actual: &[(PointOffsetType, ScoreType)],
+    expected: &[(PointOffsetType, ScoreType)],
+) {
+    assert_eq!(actual.len(), expected.len());
+    for ((actual_id, actual_score), (expected_id, expected_score)) in actual.iter().zip(expected) {
+        assert_eq!(actual_id, expected_id);
+        assert!((actual_score - expected_score).abs() < 1e-3);
+    }
+}
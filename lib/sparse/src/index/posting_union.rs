@@ -0,0 +1,106 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use common::types::{PointOffsetType, ScoreType};
+
+use crate::common::types::DimWeight;
+use crate::index::posting_list::PostingListIterator;
+
+/// Merges several posting lists by ascending id, yielding `(record_id, score)`
+/// where `score` is the sum of `weight * query_weight` over all lists containing the id.
+pub struct PostingUnionIterator<'a> {
+    /// Posting list iterators with the query weight of their dimension
+    postings: Vec<(PostingListIterator<'a>, DimWeight)>,
+    /// Current id of each non-exhausted posting, smallest on top
+    heap: BinaryHeap<Reverse<(PointOffsetType, usize)>>,
+}
+
+impl<'a> PostingUnionIterator<'a> {
+    pub fn new(postings: Vec<(PostingListIterator<'a>, DimWeight)>) -> Self {
+        let heap = postings
+            .iter()
+            .enumerate()
+            .filter_map(|(index, (iterator, _))| {
+                iterator.peek().map(|e| Reverse((e.record_id, index)))
+            })
+            .collect();
+        Self { postings, heap }
+    }
+}
+
+impl<'a> Iterator for PostingUnionIterator<'a> {
+    type Item = (PointOffsetType, ScoreType);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Reverse((record_id, _)) = *self.heap.peek()?;
+
+        // combine all postings positioned at the same id
+        let mut score = 0.0;
+        while let Some(&Reverse((id, index))) = self.heap.peek() {
+            if id != record_id {
+                break;
+            }
+            self.heap.pop();
+
+            let (iterator, query_weight) = &mut self.postings[index];
+            let element = iterator
+                .next()
+                .expect("posting in the heap has a current element");
+            score += element.weight * *query_weight;
+
+            if let Some(next_element) = iterator.peek() {
+                self.heap.push(Reverse((next_element.record_id, index)));
+            }
+        }
+
+        Some((record_id, score))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::index::posting_list::PostingList;
+
+    #[test]
+    fn test_posting_union_iterator() {
+        let posting_lists = [
+            PostingList::from(vec![(1, 1.0), (3, 2.0), (5, 3.0), (9, 1.0)]),
+            PostingList::from(vec![(2, 0.5), (3, 1.0), (9, 4.0)]),
+            PostingList::from(vec![(3, 2.0), (4, 1.0), (5, 1.0), (10, 2.0)]),
+        ];
+        let query_weights = [1.0, 2.0, 0.5];
+
+        let union: Vec<_> = PostingUnionIterator::new(
+            posting_lists
+                .iter()
+                .zip(query_weights)
+                .map(|(list, weight)| (PostingListIterator::new(&list.elements), weight))
+                .collect(),
+        )
+        .collect();
+
+        // brute force accumulation
+        let mut expected: HashMap<PointOffsetType, ScoreType> = HashMap::new();
+        for (list, query_weight) in posting_lists.iter().zip(query_weights) {
+            for element in &list.elements {
+                *expected.entry(element.record_id).or_default() += element.weight * query_weight;
+            }
+        }
+        let mut expected: Vec<_> = expected.into_iter().collect();
+        expected.sort_unstable_by_key(|(id, _)| *id);
+
+        assert_eq!(union, expected);
+    }
+
+    #[test]
+    fn test_posting_union_iterator_empty() {
+        let empty = PostingList::default();
+        let mut union =
+            PostingUnionIterator::new(vec![(PostingListIterator::new(&empty.elements), 1.0)]);
+        assert!(union.next().is_none());
+        assert!(PostingUnionIterator::new(vec![]).next().is_none());
+    }
+}
@@ -0,0 +1,58 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use common::types::{PointOffsetType, ScoreType, ScoredPointOffset};
+
+use crate::common::types::DimWeight;
+use crate::index::posting_list::PostingElement;
+
+/// Upper bound of the contribution of the current and all following elements of a posting list.
+///
+/// Clamped at zero, as an id missing from the list contributes nothing even if all stored weights
+/// are negative. Only valid for non-negative `query_weight`.
+pub(crate) fn max_contribution(element: &PostingElement, query_weight: DimWeight) -> ScoreType {
+    (element.weight.max(element.max_next_weight) * query_weight).max(0.0)
+}
+
+/// Keeps the `top_k` best scored ids with an exact threshold.
+pub(crate) struct TopHeap {
+    top_k: usize,
+    heap: BinaryHeap<Reverse<ScoredPointOffset>>,
+}
+
+impl TopHeap {
+    pub(crate) fn new(top_k: usize) -> Self {
+        Self {
+            top_k,
+            heap: BinaryHeap::with_capacity(top_k + 1),
+        }
+    }
+
+    /// Minimum score required to enter the heap, negative infinity until it is full.
+    pub(crate) fn threshold(&self) -> ScoreType {
+        if self.heap.len() < self.top_k {
+            return ScoreType::NEG_INFINITY;
+        }
+        self.heap
+            .peek()
+            .map_or(ScoreType::INFINITY, |Reverse(worst)| worst.score)
+    }
+
+    pub(crate) fn push(&mut self, idx: PointOffsetType, score: ScoreType) {
+        if score > self.threshold() {
+            self.heap.push(Reverse(ScoredPointOffset { idx, score }));
+            if self.heap.len() > self.top_k {
+                self.heap.pop();
+            }
+        }
+    }
+
+    /// Results ordered by descending score.
+    pub(crate) fn into_vec(self) -> Vec<(PointOffsetType, ScoreType)> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse(point)| (point.idx, point.score))
+            .collect()
+    }
+}
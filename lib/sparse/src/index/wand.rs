@@ -0,0 +1,144 @@
+use common::types::{PointOffsetType, ScoreType};
+
+use crate::common::types::DimWeight;
+use crate::index::posting_list::PostingListIterator;
+use crate::index::posting_union::PostingUnionIterator;
+use crate::index::top_k::{max_contribution, TopHeap};
+
+/// Finds the `top_k` ids with the highest sum of `weight * query_weight` over the posting lists,
+/// ordered by descending score.
+///
+/// Implements WAND: postings are ordered by their current id and the first id at which the
+/// accumulated `max_next_weight` bounds can beat the current threshold becomes the pivot.
+/// Ids before the pivot can't enter the top and are skipped without being scored.
+///
+/// Pruning relies on non-negative query weights, otherwise all ids are scored.
+pub fn search(
+    mut postings: Vec<(PostingListIterator<'_>, DimWeight)>,
+    top_k: usize,
+) -> Vec<(PointOffsetType, ScoreType)> {
+    let mut top = TopHeap::new(top_k);
+    if top_k == 0 {
+        return top.into_vec();
+    }
+
+    if postings.iter().any(|(_, query_weight)| *query_weight < 0.0) {
+        for (record_id, score) in PostingUnionIterator::new(postings) {
+            top.push(record_id, score);
+        }
+        return top.into_vec();
+    }
+
+    postings.retain(|(iterator, _)| iterator.len_to_end() > 0);
+    while !postings.is_empty() {
+        postings.sort_unstable_by_key(|(iterator, _)| current_id(iterator));
+
+        // find the first posting at which the accumulated upper bound beats the threshold
+        let threshold = top.threshold();
+        let mut upper_bound = 0.0;
+        let pivot = postings.iter().position(|(iterator, query_weight)| {
+            upper_bound += max_contribution(iterator.peek().unwrap(), *query_weight);
+            upper_bound > threshold
+        });
+        let Some(pivot) = pivot else {
+            // even the sum of all upper bounds can't enter the top
+            break;
+        };
+        let pivot_id = current_id(&postings[pivot].0);
+
+        if current_id(&postings[0].0) == pivot_id {
+            // all postings up to the pivot are at the pivot id, score it fully
+            let mut score = 0.0;
+            for (iterator, query_weight) in postings.iter_mut() {
+                if current_id(iterator) != pivot_id {
+                    break;
+                }
+                score += iterator.next().unwrap().weight * *query_weight;
+            }
+            top.push(pivot_id, score);
+        } else {
+            // ids before the pivot can't enter the top
+            for (iterator, _) in &mut postings[..pivot] {
+                iterator.skip_to(pivot_id);
+            }
+        }
+
+        postings.retain(|(iterator, _)| iterator.len_to_end() > 0);
+    }
+
+    top.into_vec()
+}
+
+fn current_id(iterator: &PostingListIterator) -> PointOffsetType {
+    iterator
+        .peek()
+        .expect("exhausted postings are removed")
+        .record_id
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+    use crate::index::posting_list::PostingList;
+    use crate::index::posting_list_fixture::{
+        assert_same_top, brute_force_top, iterators, random_posting_lists,
+    };
+
+    #[test]
+    fn test_wand_matches_brute_force() {
+        let mut rnd = StdRng::seed_from_u64(42);
+        for (terms, max_len, max_id, top_k) in [
+            (1, 100, 1_000, 10),
+            (3, 100, 200, 5),
+            (5, 1_000, 2_000, 10),
+            (8, 500, 10_000, 1),
+            (4, 50, 100, 1_000),
+        ]
+        .into_iter()
+        // many small queries to hit negative stored weights near the threshold
+        .chain(itertools::repeat_n((3, 20, 50, 1), 300))
+        {
+            let posting_lists = random_posting_lists(&mut rnd, terms, max_len, max_id);
+            let query_weights: Vec<DimWeight> =
+                (0..terms).map(|_| rnd.gen_range(0.0..2.0)).collect();
+
+            let result = search(iterators(&posting_lists, &query_weights), top_k);
+            let expected = brute_force_top(&posting_lists, &query_weights, top_k);
+            assert_same_top(&result, &expected);
+        }
+    }
+
+    #[test]
+    fn test_wand_negative_query_weights() {
+        let mut rnd = StdRng::seed_from_u64(42);
+        let posting_lists = random_posting_lists(&mut rnd, 4, 100, 300);
+        let query_weights = [1.0, -0.5, 2.0, -1.0];
+
+        let result = search(iterators(&posting_lists, &query_weights), 10);
+        let expected = brute_force_top(&posting_lists, &query_weights, 10);
+        assert_same_top(&result, &expected);
+    }
+
+    #[test]
+    fn test_wand_negative_stored_weights() {
+        // id 2 is missing from the negative list, whose bound must not hide it
+        let posting_lists = [
+            PostingList::from(vec![(0, -5.0), (1, -5.0)]),
+            PostingList::from(vec![(0, 6.0), (2, 3.0)]),
+        ];
+        let result = search(iterators(&posting_lists, &[1.0, 1.0]), 1);
+        assert_eq!(result, vec![(2, 3.0)]);
+    }
+
+    #[test]
+    fn test_wand_empty() {
+        let empty = PostingList::default();
+        let list = PostingList::from(vec![(1, 1.0), (2, 2.0)]);
+        assert!(search(vec![], 10).is_empty());
+        assert!(search(iterators(&[empty], &[1.0]), 10).is_empty());
+        assert!(search(iterators(&[list], &[1.0]), 0).is_empty());
+    }
+}